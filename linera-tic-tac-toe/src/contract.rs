@@ -1,13 +1,17 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
-mod state;
-
-use self::state::{Game, GameStatus, TicTacToeState};
 use linera_sdk::{
-    base::{AccountOwner, WithContractAbi},
+    base::{AccountOwner, ChainId, WithContractAbi},
     Contract, ContractRuntime,
 };
-use tic_tac_toe::{Message, Operation, TicTacToeAbi};
+use tic_tac_toe::{
+    state::{
+        parse_cell, validate_display_name, DrawReason, FinishedGame, Game, GameError, GameExport,
+        GameIdReservation, GameResult, GameStatus, Invitation, Series, TicTacToeState, Tournament,
+        MAX_ACTIVE_GAMES,
+    },
+    Message, Operation, TicTacToeAbi, TicTacToeParameters,
+};
 
 pub struct TicTacToeContract;
 
@@ -17,115 +21,1783 @@ impl WithContractAbi for TicTacToeContract {
     type Abi = TicTacToeAbi;
 }
 
-impl Contract for TicTacToeContract {
-    type Message = Message;
-    type Parameters = ();
-    type State = TicTacToeState;
+impl TicTacToeContract {
+    /// Store a newly created game and announce it to subscribers
+    async fn create_game(
+        runtime: &ContractRuntime<Self>,
+        owner: AccountOwner,
+        game: Game,
+    ) -> Result<u64, GameError> {
+        let mut state = runtime.state_mut().await;
+        state.check_active_limit(&owner, MAX_ACTIVE_GAMES).await?;
+        let mut reservation = state
+            .reserve_game_ids(game.chain_id, &[game.player_x])
+            .await?;
+        let max_games = runtime.application_parameters().max_games;
+        let evicted = state.game_to_evict(max_games).await?;
+        // The last check, which only records the creation once it passes
+        state.record_creation(&owner, runtime.system_time()).await?;
+        if let Some(evicted) = evicted {
+            Self::evict_game(&mut state, evicted);
+        }
+        let passcode_hash = game.passcode_hash;
+        let setup = game.setup();
+        let game_id = state.insert_reserved_game(&mut reservation, game);
 
-    async fn load(runtime: ContractRuntime<Self>) -> Self {
-        TicTacToeContract
+        // Send cross-chain message about new game
+        runtime
+            .prepare_message(Message::GameCreated {
+                game_id,
+                creator: owner,
+                passcode_hash,
+                setup,
+            })
+            .send_to_subscribers();
+
+        Self::log_event(log::Level::Info, "GameCreated", Some(game_id), Some(owner), "ok");
+        Ok(game_id)
     }
 
-    async fn instantiate(&mut self, _runtime: ContractRuntime<Self>, _argument: ()) {}
+    /// Make a move for a player, announce it and settle any series it decides
+    async fn play_move(
+        runtime: &ContractRuntime<Self>,
+        game_id: u64,
+        player: AccountOwner,
+        row: usize,
+        col: usize,
+    ) -> Result<(), GameError> {
+        let mut state = runtime.state_mut().await;
 
-    async fn execute_operation(
-        &mut self,
+        if let Some(game) = state
+            .games
+            .get_mut(&game_id)
+            .await
+            .expect("Failed to load game")
+        {
+            // Refuse to build on a game whose stored state is inconsistent
+            let result = game
+                .validate()
+                .and_then(|()| game.make_move(&player, row, col, runtime.system_time()));
+            // A move rejected by losing the game still ends it, so the loss is kept and
+            // reported once the game has been settled
+            let rejection = match result {
+                Ok(()) => {
+                    // Send cross-chain message about move
+                    runtime
+                        .prepare_message(Message::MoveMade {
+                            game_id,
+                            player,
+                            row,
+                            col,
+                        })
+                        .send_to_subscribers();
+
+                    let cell = format!("cell:({}, {})", row, col);
+                    Self::log_event(
+                        log::Level::Info,
+                        "MoveMade",
+                        Some(game_id),
+                        Some(player),
+                        &cell,
+                    );
+                    Self::notify_turn(runtime, game_id, game);
+
+                    // Check if game ended
+                    match &game.status {
+                        GameStatus::Won(winner) => {
+                            let result = format!("won_by:{}", winner.symbol());
+                            Self::log_event(
+                                log::Level::Info,
+                                "GameWon",
+                                Some(game_id),
+                                None,
+                                &result,
+                            );
+                        }
+                        GameStatus::Draw(reason) => {
+                            let result = format!("draw:{}", reason.label());
+                            Self::log_event(
+                                log::Level::Info,
+                                "GameDrawn",
+                                Some(game_id),
+                                None,
+                                &result,
+                            );
+                        }
+                        _ => {}
+                    }
+                    None
+                }
+                Err(GameError::TimedOut) => {
+                    Self::log_event(
+                        log::Level::Warn,
+                        "TimedOut",
+                        Some(game_id),
+                        Some(player),
+                        "lost",
+                    );
+                    Some(GameError::TimedOut)
+                }
+                Err(e) if game.forfeit_illegal_move(&player, &e, runtime.system_time()) => {
+                    // Send cross-chain message about the forfeit
+                    runtime
+                        .prepare_message(Message::GameForfeited {
+                            game_id,
+                            offender: player,
+                            reason: e.to_string(),
+                        })
+                        .send_to_subscribers();
+
+                    let result = format!("error:{:?}", e.to_string());
+                    Self::log_event(
+                        log::Level::Warn,
+                        "Forfeited",
+                        Some(game_id),
+                        Some(player),
+                        &result,
+                    );
+                    Some(GameError::Forfeited)
+                }
+                Err(e) => return Err(e),
+            };
+
+            drop(state);
+            Self::settle_game(runtime, game_id).await;
+            rejection.map_or(Ok(()), Err)
+        } else {
+            Err(GameError::GameNotFound)
+        }
+    }
+
+    /// Reserve the IDs of new games and find the games to evict to make room for them
+    ///
+    /// Nothing is written, so a failure leaves the state as it was.
+    async fn prepare_new_games(
+        runtime: &ContractRuntime<Self>,
+        state: &TicTacToeState,
+        chain_id: ChainId,
+        creators: &[AccountOwner],
+    ) -> Result<(GameIdReservation, Vec<u64>), GameError> {
+        let reservation = state.reserve_game_ids(chain_id, creators).await?;
+        let max_games = runtime.application_parameters().max_games;
+        let evicted = state.games_to_evict(max_games, creators.len()).await?;
+        Ok((reservation, evicted))
+    }
+
+    /// Remove a finished game to make room for a new one, once every check for the new game has passed
+    fn evict_game(state: &mut TicTacToeState, game_id: u64) {
+        state
+            .games
+            .remove(&game_id)
+            .expect("Failed to remove game");
+        Self::log_event(log::Level::Info, "GameEvicted", Some(game_id), None, "ok");
+    }
+
+    /// Take the O seat of a game, asking the host chain to seat the caller if the game is a mirror
+    ///
+    /// With `matched`, the chain that seats the player also announces the pairing.
+    async fn join_game(
+        runtime: &ContractRuntime<Self>,
+        owner: AccountOwner,
+        game_id: u64,
+        passcode: Option<String>,
+        matched: bool,
+    ) -> Result<(), GameError> {
+        let mut state = runtime.state_mut().await;
+        state.check_active_limit(&owner, MAX_ACTIVE_GAMES).await?;
+
+        let Some(game) = state
+            .games
+            .get_mut(&game_id)
+            .await
+            .expect("Failed to load game")
+        else {
+            return Err(GameError::GameNotFound);
+        };
+
+        // A mirrored game can only be joined on the chain hosting it
+        if game.chain_id != runtime.chain_id() {
+            runtime
+                .prepare_message(Message::RequestJoin {
+                    game_id,
+                    player: owner,
+                    passcode,
+                    matched,
+                })
+                .with_authentication()
+                .send_to(game.chain_id);
+
+            let result = format!("host:{}", game.chain_id);
+            Self::log_event(log::Level::Info, "JoinRequested", Some(game_id), Some(owner), &result);
+            return Ok(());
+        }
+
+        game.join(owner, runtime.chain_id(), passcode.as_deref(), runtime.system_time())?;
+
+        // Send cross-chain message about player joining
+        runtime
+            .prepare_message(Message::PlayerJoined {
+                game_id,
+                player: owner,
+            })
+            .send_to_subscribers();
+        if matched {
+            runtime
+                .prepare_message(Message::Matched {
+                    game_id,
+                    player_x: game.player_x,
+                    player_o: owner,
+                })
+                .send_to_subscribers();
+        }
+
+        Self::log_event(log::Level::Info, "PlayerJoined", Some(game_id), Some(owner), "ok");
+        Ok(())
+    }
+
+    /// Tell the player to move that it is their turn, unless the game is over or the bot is to move
+    ///
+    /// Messages only travel to subscribers, so the notification is addressed by its
+    /// `player` field and clients pick out the ones meant for them.
+    fn notify_turn(runtime: &ContractRuntime<Self>, game_id: u64, game: &Game) {
+        if game.status != GameStatus::InProgress {
+            return;
+        }
+        let Some(player) = game.account_for(game.current_player) else {
+            return;
+        };
+        if player == Self::bot_owner(runtime) {
+            return;
+        }
+        runtime
+            .prepare_message(Message::YourTurn { game_id, player })
+            .send_to_subscribers();
+    }
+
+    /// Account holding the seat of the built-in bot opponent
+    fn bot_owner(runtime: &ContractRuntime<Self>) -> AccountOwner {
+        AccountOwner::Application(runtime.application_id().forget_abi())
+    }
+
+    /// Remove a game nobody has joined yet and announce it to subscribers
+    async fn abort_game(
+        runtime: &ContractRuntime<Self>,
+        owner: AccountOwner,
+        game_id: u64,
+    ) -> Result<(), GameError> {
+        let mut state = runtime.state_mut().await;
+
+        if let Some(game) = state
+            .games
+            .get(&game_id)
+            .await
+            .expect("Failed to load game")
+        {
+            game.can_abort(&owner)?;
+        } else {
+            return Err(GameError::GameNotFound);
+        }
+
+        state
+            .games
+            .remove(&game_id)
+            .expect("Failed to remove game");
+
+        // Send cross-chain message about the aborted game
+        runtime
+            .prepare_message(Message::GameAborted { game_id })
+            .send_to_subscribers();
+
+        Self::log_event(log::Level::Info, "GameAborted", Some(game_id), Some(owner), "ok");
+        Ok(())
+    }
+
+    /// Move a finished game from the active games into the archive
+    async fn archive_game(
+        runtime: &ContractRuntime<Self>,
+        owner: AccountOwner,
+        game_id: u64,
+    ) -> Result<(), GameError> {
+        let mut state = runtime.state_mut().await;
+
+        let Some(game) = state
+            .games
+            .get(&game_id)
+            .await
+            .expect("Failed to load game")
+        else {
+            return Err(GameError::GameNotFound);
+        };
+
+        game.can_archive(&owner)?;
+
+        state
+            .games
+            .remove(&game_id)
+            .expect("Failed to remove game");
+        state
+            .archived_games
+            .insert(&game_id, game)
+            .expect("Failed to store archived game");
+
+        Self::log_event(log::Level::Info, "GameArchived", Some(game_id), Some(owner), "ok");
+        Ok(())
+    }
+
+    /// Announce the result of a game that has just ended and settle any series it decides
+    async fn settle_game(runtime: &ContractRuntime<Self>, game_id: u64) {
+        let state = runtime.state_mut().await;
+        let game = state.games.get(&game_id).await.expect("Failed to load game");
+        drop(state);
+
+        let Some((result, game)) = game.and_then(|game| Some((game.result()?, game))) else {
+            return;
+        };
+
+        // Send cross-chain message with the authoritative result
+        runtime
+            .prepare_message(Message::GameEnded {
+                game_id,
+                player_x: game.player_x,
+                player_o: game.player_o,
+                result,
+            })
+            .send_to_subscribers();
+
+        Self::advance_series(runtime, game_id).await;
+        Self::advance_tournament(runtime, game_id).await;
+    }
+
+    /// Record the result of a finished series game and start the next game if needed
+    async fn advance_series(runtime: &ContractRuntime<Self>, game_id: u64) {
+        let mut state = runtime.state_mut().await;
+        let Some(game) = state.games.get(&game_id).await.expect("Failed to load game") else {
+            return;
+        };
+        let Some(series_id) = game.series_id else {
+            return;
+        };
+        if !game.is_finished() {
+            return;
+        }
+
+        let mut series = state
+            .series
+            .get(&series_id)
+            .await
+            .expect("Failed to load series")
+            .expect("Series not found");
+        if series.is_decided() || series.game_ids.last() != Some(&game_id) {
+            return;
+        }
+
+        let winner = match game.status {
+            GameStatus::Won(player) => game.account_for(player),
+            _ => None,
+        };
+        series.record_result(winner);
+
+        if series.is_decided() {
+            let result = format!("series_decided:{}", series_id);
+            Self::log_event(log::Level::Info, "SeriesGameFinished", Some(game_id), None, &result);
+        } else {
+            let (player_x, player_o) = series.next_seats();
+            let prepared =
+                Self::prepare_new_games(runtime, &state, game.chain_id, &[player_x]).await;
+            let (mut reservation, evicted) = match prepared {
+                Ok(prepared) => prepared,
+                Err(e) => {
+                    // The result still counts, but the series cannot go on without a new game
+                    let result = format!("error:{:?}", e.to_string());
+                    Self::log_event(
+                        log::Level::Error,
+                        "SeriesGameStarted",
+                        Some(game_id),
+                        None,
+                        &result,
+                    );
+                    state
+                        .series
+                        .insert(&series_id, series)
+                        .expect("Failed to store series");
+                    return;
+                }
+            };
+            for evicted in evicted {
+                Self::evict_game(&mut state, evicted);
+            }
+            let mut next_game =
+                Game::new_between(player_x, player_o, game.chain_id, runtime.system_time())
+                    .expect("Series players must differ");
+            next_game.series_id = Some(series_id);
+            let setup = next_game.setup();
+            let next_game_id = state.insert_reserved_game(&mut reservation, next_game);
+            series.game_ids.push(next_game_id);
+
+            // Send cross-chain messages about the next series game
+            runtime
+                .prepare_message(Message::GameCreated {
+                    game_id: next_game_id,
+                    creator: player_x,
+                    passcode_hash: None,
+                    setup,
+                })
+                .send_to_subscribers();
+            runtime
+                .prepare_message(Message::PlayerJoined {
+                    game_id: next_game_id,
+                    player: player_o,
+                })
+                .send_to_subscribers();
+
+            let result = format!("series:{}", series_id);
+            Self::log_event(
+                log::Level::Info,
+                "SeriesGameStarted",
+                Some(next_game_id),
+                None,
+                &result,
+            );
+        }
+
+        state
+            .series
+            .insert(&series_id, series)
+            .expect("Failed to store series");
+    }
+
+    /// Keep the result of a game hosted elsewhere, ignoring every other message
+    async fn record_result(runtime: &ContractRuntime<Self>, host_chain: ChainId, message: Message) {
+        let Message::GameEnded {
+            game_id,
+            player_x,
+            player_o,
+            result,
+        } = message
+        else {
+            return;
+        };
+
+        let mut state = runtime.state_mut().await;
+        state
+            .results
+            .insert(
+                &game_id,
+                FinishedGame {
+                    player_x,
+                    player_o,
+                    result,
+                    chain_id: host_chain,
+                    ended_at: runtime.system_time(),
+                },
+            )
+            .expect("Failed to store result");
+        let result = format!("{:?}", result);
+        Self::log_event(log::Level::Info, "ResultRecorded", Some(game_id), None, &result);
+    }
+
+    /// Start the game deciding a tournament match under a reserved ID and announce it to subscribers
+    #[allow(clippy::too_many_arguments)]
+    fn start_tournament_game(
+        runtime: &ContractRuntime<Self>,
+        state: &mut TicTacToeState,
+        reservation: &mut GameIdReservation,
+        tournament_id: u64,
+        tournament: &mut Tournament,
+        slot: usize,
+        player_x: AccountOwner,
+        player_o: AccountOwner,
+    ) {
+        let mut game = Game::new_between(
+            player_x,
+            player_o,
+            runtime.chain_id(),
+            runtime.system_time(),
+        )
+        .expect("Tournament players must differ");
+        game.tournament_id = Some(tournament_id);
+        let setup = game.setup();
+        let game_id = state.insert_reserved_game(reservation, game);
+        tournament.assign_game(slot, game_id);
+
+        // Send cross-chain messages about the tournament game
+        runtime
+            .prepare_message(Message::GameCreated {
+                game_id,
+                creator: player_x,
+                passcode_hash: None,
+                setup,
+            })
+            .send_to_subscribers();
+        runtime
+            .prepare_message(Message::PlayerJoined {
+                game_id,
+                player: player_o,
+            })
+            .send_to_subscribers();
+
+        let result = format!(
+            "tournament:{} round:{} match:{}",
+            tournament_id, tournament.round, slot
+        );
+        Self::log_event(log::Level::Info, "TournamentGameStarted", Some(game_id), None, &result);
+    }
+
+    /// Start a game for each of the given tournament matches, as (slot, player X, player O)
+    ///
+    /// The IDs of all the games are reserved and room is made for them first, so either
+    /// every game starts or nothing is stored.
+    async fn start_tournament_games(
+        runtime: &ContractRuntime<Self>,
+        state: &mut TicTacToeState,
+        tournament_id: u64,
+        tournament: &mut Tournament,
+        matches: &[(usize, AccountOwner, AccountOwner)],
+    ) -> Result<(), GameError> {
+        let creators: Vec<_> = matches.iter().map(|&(_, player_x, _)| player_x).collect();
+        let (mut reservation, evicted) =
+            Self::prepare_new_games(runtime, state, runtime.chain_id(), &creators).await?;
+        for evicted in evicted {
+            Self::evict_game(state, evicted);
+        }
+        for &(slot, player_x, player_o) in matches {
+            Self::start_tournament_game(
+                runtime,
+                state,
+                &mut reservation,
+                tournament_id,
+                tournament,
+                slot,
+                player_x,
+                player_o,
+            );
+        }
+        Ok(())
+    }
+
+    /// Advance the winner of a finished tournament game and start the next round once it is complete
+    async fn advance_tournament(runtime: &ContractRuntime<Self>, game_id: u64) {
+        let mut state = runtime.state_mut().await;
+        let Some(game) = state.games.get(&game_id).await.expect("Failed to load game") else {
+            return;
+        };
+        let Some(tournament_id) = game.tournament_id else {
+            return;
+        };
+        if !game.is_finished() {
+            return;
+        }
+
+        let mut tournament = state
+            .tournaments
+            .get(&tournament_id)
+            .await
+            .expect("Failed to load tournament")
+            .expect("Tournament not found");
+        let Some(slot) = tournament.match_of(game_id) else {
+            return;
+        };
+
+        let player_o = game.player_o.expect("Tournament games are fully seated");
+        let started = match game.status {
+            GameStatus::Won(player) => {
+                let winner = game.account_for(player).expect("Winner must be seated");
+                tournament.record_winner(slot, winner);
+                match tournament.champion() {
+                    Some(champion) => {
+                        let result = format!("tournament_won:{}", tournament_id);
+                        Self::log_event(
+                            log::Level::Info,
+                            "TournamentGameFinished",
+                            Some(game_id),
+                            Some(champion),
+                            &result,
+                        );
+                        Ok(())
+                    }
+                    None => {
+                        let matches = tournament.pending_matches();
+                        Self::start_tournament_games(
+                            runtime,
+                            &mut state,
+                            tournament_id,
+                            &mut tournament,
+                            &matches,
+                        )
+                        .await
+                    }
+                }
+            }
+            // A drawn match is replayed with the seats swapped until someone wins
+            _ => {
+                Self::start_tournament_games(
+                    runtime,
+                    &mut state,
+                    tournament_id,
+                    &mut tournament,
+                    &[(slot, player_o, game.player_x)],
+                )
+                .await
+            }
+        };
+        // The result still counts, but the matches left without a game cannot be played
+        if let Err(e) = started {
+            let result = format!("tournament:{} error:{:?}", tournament_id, e.to_string());
+            Self::log_event(
+                log::Level::Error,
+                "TournamentGameStarted",
+                Some(game_id),
+                None,
+                &result,
+            );
+        }
+
+        state
+            .tournaments
+            .insert(&tournament_id, tournament)
+            .expect("Failed to store tournament");
+    }
+
+    /// Logs an event with consistent `key=value` fields so entries can be filtered by game or player
+    fn log_event(
+        level: log::Level,
+        event: &str,
+        game_id: Option<u64>,
+        owner: Option<AccountOwner>,
+        result: &str,
+    ) {
+        let game_id = game_id.map_or_else(|| "-".to_string(), |id| id.to_string());
+        let owner = owner.map_or_else(|| "-".to_string(), |owner| owner.to_string());
+        log::log!(
+            level,
+            "event={} game_id={} owner={} result={}",
+            event,
+            game_id,
+            owner,
+            result
+        );
+    }
+
+    /// Returns the variant name of an operation and the game it refers to, if any
+    fn describe_operation(operation: &Operation) -> (&'static str, Option<u64>) {
+        match operation {
+            Operation::CreateGame => ("CreateGame", None),
+            Operation::CreateGameSized { .. } => ("CreateGameSized", None),
+            Operation::CreateGameCustom { .. } => ("CreateGameCustom", None),
+            Operation::CreateGameWithOpponent { .. } => ("CreateGameWithOpponent", None),
+            Operation::ChallengePlayer { .. } => ("ChallengePlayer", None),
+            Operation::AcceptChallenge { .. } => ("AcceptChallenge", None),
+            Operation::DeclineChallenge { .. } => ("DeclineChallenge", None),
+            Operation::CreateGameTitled { .. } => ("CreateGameTitled", None),
+            Operation::CreateGameHandicap { .. } => ("CreateGameHandicap", None),
+            Operation::JoinGame { game_id } => ("JoinGame", Some(*game_id)),
+            Operation::JoinGamePrivate { game_id, .. } => ("JoinGamePrivate", Some(*game_id)),
+            Operation::CreateGamePrivate { .. } => ("CreateGamePrivate", None),
+            Operation::MakeMove { game_id, .. } => ("MakeMove", Some(*game_id)),
+            Operation::MakeMoveNotation { game_id, .. } => ("MakeMoveNotation", Some(*game_id)),
+            Operation::MakeMoves { game_id, .. } => ("MakeMoves", Some(*game_id)),
+            Operation::ResignGame { game_id } => ("ResignGame", Some(*game_id)),
+            Operation::FinalizeResign { game_id } => ("FinalizeResign", Some(*game_id)),
+            Operation::OfferDraw { game_id } => ("OfferDraw", Some(*game_id)),
+            Operation::RespondDraw { game_id, .. } => ("RespondDraw", Some(*game_id)),
+            Operation::RequestUndo { game_id } => ("RequestUndo", Some(*game_id)),
+            Operation::RespondUndo { game_id, .. } => ("RespondUndo", Some(*game_id)),
+            Operation::RequestRematch { game_id } => ("RequestRematch", Some(*game_id)),
+            Operation::ClaimDeadDraw { game_id } => ("ClaimDeadDraw", Some(*game_id)),
+            Operation::ClaimTimeoutWin { game_id } => ("ClaimTimeoutWin", Some(*game_id)),
+            Operation::AbortGame { game_id } => ("AbortGame", Some(*game_id)),
+            Operation::TransferSeat { game_id, .. } => ("TransferSeat", Some(*game_id)),
+            Operation::AdoptWaitingGame { game_id } => ("AdoptWaitingGame", Some(*game_id)),
+            Operation::ResetGame { game_id } => ("ResetGame", Some(*game_id)),
+            Operation::ArchiveGame { game_id } => ("ArchiveGame", Some(*game_id)),
+            Operation::DeleteGame { game_id } => ("DeleteGame", Some(*game_id)),
+            Operation::SweepInactive { .. } => ("SweepInactive", None),
+            Operation::LeaveGame { game_id } => ("LeaveGame", Some(*game_id)),
+            Operation::Spectate { game_id } => ("Spectate", Some(*game_id)),
+            Operation::SetDisplayName { .. } => ("SetDisplayName", None),
+            Operation::CreateGameVsBot { .. } => ("CreateGameVsBot", None),
+            Operation::PlayBot { game_id } => ("PlayBot", Some(*game_id)),
+            Operation::CreateSeries { .. } => ("CreateSeries", None),
+            Operation::WithdrawFromSeries { .. } => ("WithdrawFromSeries", None),
+            Operation::CreateTournament { .. } => ("CreateTournament", None),
+            Operation::EnqueueMatch => ("EnqueueMatch", None),
+            Operation::CreateFromPosition { .. } => ("CreateFromPosition", None),
+            Operation::ImportGame { .. } => ("ImportGame", None),
+        }
+    }
+
+    /// Returns the variant name of a message
+    fn message_name(message: &Message) -> &'static str {
+        match message {
+            Message::GameCreated { .. } => "GameCreated",
+            Message::PlayerJoined { .. } => "PlayerJoined",
+            Message::MoveMade { .. } => "MoveMade",
+            Message::RequestJoin { .. } => "RequestJoin",
+            Message::YourTurn { .. } => "YourTurn",
+            Message::MoveUndone { .. } => "MoveUndone",
+            Message::GameForfeited { .. } => "GameForfeited",
+            Message::GameEnded { .. } => "GameEnded",
+            Message::GameResigned { .. } => "GameResigned",
+            Message::DrawAgreed { .. } => "DrawAgreed",
+            Message::RematchStarted { .. } => "RematchStarted",
+            Message::SeriesWithdrawn { .. } => "SeriesWithdrawn",
+            Message::GameAborted { .. } => "GameAborted",
+            Message::SeatTransferred { .. } => "SeatTransferred",
+            Message::CreatorReplaced { .. } => "CreatorReplaced",
+            Message::GameReset { .. } => "GameReset",
+            Message::GameDeleted { .. } => "GameDeleted",
+            Message::PlayerLeft { .. } => "PlayerLeft",
+            Message::SpectatorJoined { .. } => "SpectatorJoined",
+            Message::Matched { .. } => "Matched",
+        }
+    }
+
+    async fn handle_operation(
         runtime: ContractRuntime<Self>,
+        owner: AccountOwner,
         operation: Operation,
-    ) -> Self::Response {
-        let owner = runtime
-            .authenticated_signer()
-            .expect("Missing authentication");
-
+    ) -> Result<(), GameError> {
         match operation {
             Operation::CreateGame => {
+                let game = Game::new(owner, runtime.chain_id(), runtime.system_time());
+                Self::create_game(&runtime, owner, game).await?;
+            }
+
+            Operation::CreateGameWithOpponent { opponent } => {
+                let game = Game::new_between(
+                    owner,
+                    opponent,
+                    runtime.chain_id(),
+                    runtime.system_time(),
+                )?;
+                // The creator's own limit is checked when the game is created
+                let state = runtime.state_mut().await;
+                state.check_active_limit(&opponent, MAX_ACTIVE_GAMES).await?;
+                drop(state);
+                let game_id = Self::create_game(&runtime, owner, game).await?;
+
+                // Send cross-chain message about the opponent taking their seat
+                runtime
+                    .prepare_message(Message::PlayerJoined {
+                        game_id,
+                        player: opponent,
+                    })
+                    .send_to_subscribers();
+
+                let result = format!("opponent:{}", opponent);
+                Self::log_event(
+                    log::Level::Info,
+                    "ChallengeGameCreated",
+                    Some(game_id),
+                    Some(owner),
+                    &result,
+                );
+            }
+
+            Operation::ChallengePlayer { opponent } => {
+                let now = runtime.system_time();
+                let invitation = Invitation::new(owner, opponent, now)?;
+
+                let mut state = runtime.state_mut().await;
+                let invitation_id = *state.next_invitation_id.get();
+                let next_invitation_id = invitation_id
+                    .checked_add(1)
+                    .ok_or(GameError::IdSpaceExhausted)?;
+                // Challenges count towards the creation limit so they cannot be used to spam
+                state.record_creation(&owner, now).await?;
+                state.next_invitation_id.set(next_invitation_id);
+                state
+                    .invitations
+                    .insert(&invitation_id, invitation)
+                    .expect("Failed to store invitation");
+
+                let result = format!("invitation:{} opponent:{}", invitation_id, opponent);
+                Self::log_event(log::Level::Info, "InvitationCreated", None, Some(owner), &result);
+            }
+
+            Operation::AcceptChallenge { invitation_id } => {
                 let mut state = runtime.state_mut().await;
-                let game_id = state.next_game_id;
-                let chain_id = runtime.chain_id();
-                
-                let game = Game::new(owner, chain_id);
-                state.games.insert(game_id, game);
-                state.next_game_id += 1;
+                let Some(invitation) = state
+                    .invitations
+                    .get(&invitation_id)
+                    .await
+                    .expect("Failed to load invitation")
+                else {
+                    return Err(GameError::InvitationNotFound);
+                };
+                if invitation.challengee != owner {
+                    return Err(GameError::NotAParticipant);
+                }
+                for player in [invitation.challenger, invitation.challengee] {
+                    state.check_active_limit(&player, MAX_ACTIVE_GAMES).await?;
+                }
+
+                let game = Game::new_between(
+                    invitation.challenger,
+                    invitation.challengee,
+                    runtime.chain_id(),
+                    runtime.system_time(),
+                )
+                .expect("Invitations are between different players");
+                let setup = game.setup();
+                let mut reservation = state
+                    .reserve_game_ids(game.chain_id, &[game.player_x])
+                    .await?;
+                let max_games = runtime.application_parameters().max_games;
+                if let Some(evicted) = state.game_to_evict(max_games).await? {
+                    Self::evict_game(&mut state, evicted);
+                }
+                let game_id = state.insert_reserved_game(&mut reservation, game);
+                state
+                    .invitations
+                    .remove(&invitation_id)
+                    .expect("Failed to remove invitation");
 
-                // Send cross-chain message about new game
+                // Send cross-chain messages about the new game
                 runtime
                     .prepare_message(Message::GameCreated {
                         game_id,
-                        creator: owner,
+                        creator: invitation.challenger,
+                        passcode_hash: None,
+                        setup,
                     })
                     .send_to_subscribers();
+                runtime
+                    .prepare_message(Message::PlayerJoined {
+                        game_id,
+                        player: invitation.challengee,
+                    })
+                    .send_to_subscribers();
+
+                let result = format!("invitation:{}", invitation_id);
+                Self::log_event(
+                    log::Level::Info,
+                    "InvitationAccepted",
+                    Some(game_id),
+                    Some(owner),
+                    &result,
+                );
+            }
+
+            Operation::DeclineChallenge { invitation_id } => {
+                let mut state = runtime.state_mut().await;
+                let Some(invitation) = state
+                    .invitations
+                    .get(&invitation_id)
+                    .await
+                    .expect("Failed to load invitation")
+                else {
+                    return Err(GameError::InvitationNotFound);
+                };
+                if invitation.challengee != owner && invitation.challenger != owner {
+                    return Err(GameError::NotAParticipant);
+                }
+
+                state
+                    .invitations
+                    .remove(&invitation_id)
+                    .expect("Failed to remove invitation");
+                let result = format!("invitation:{}", invitation_id);
+                Self::log_event(log::Level::Info, "InvitationDeclined", None, Some(owner), &result);
+            }
+
+            Operation::CreateGamePrivate { passcode } => {
+                let game = Game::new(owner, runtime.chain_id(), runtime.system_time())
+                    .with_passcode(&passcode)?;
+                Self::create_game(&runtime, owner, game).await?;
+            }
+
+            Operation::CreateGameTitled { title, description } => {
+                let game = Game::new(owner, runtime.chain_id(), runtime.system_time())
+                    .with_details(Some(title), description)?;
+                Self::create_game(&runtime, owner, game).await?;
+            }
+
+            Operation::CreateGameHandicap { handicap } => {
+                let game = Game::new(owner, runtime.chain_id(), runtime.system_time())
+                    .with_handicap(&handicap)?;
+                Self::create_game(&runtime, owner, game).await?;
+            }
 
-                log::info!("Game {} created by {:?}", game_id, owner);
+            Operation::CreateGameSized { size } => {
+                let game = Game::new_sized(owner, runtime.chain_id(), size, runtime.system_time())?;
+                Self::create_game(&runtime, owner, game).await?;
+            }
+
+            Operation::CreateGameCustom {
+                size,
+                win_length,
+                move_deadline_secs,
+                first_player,
+                time_budget_secs,
+                allow_same_chain,
+                strict,
+                competitive,
+            } => {
+                let now = runtime.system_time();
+                let game = Game::new_custom(owner, runtime.chain_id(), size, win_length, now)
+                    .and_then(|game| match move_deadline_secs {
+                        Some(secs) => game.with_move_deadline(secs),
+                        None => Ok(game),
+                    })
+                    .and_then(|game| match time_budget_secs {
+                        Some(secs) => game.with_time_budget(secs),
+                        None => Ok(game),
+                    })
+                    .map(|game| {
+                        game.with_first_player(first_player)
+                            .with_same_chain_allowed(allow_same_chain)
+                            .with_strict(strict)
+                            .with_competitive(competitive)
+                    })?;
+                Self::create_game(&runtime, owner, game).await?;
             }
 
             Operation::JoinGame { game_id } => {
+                Self::join_game(&runtime, owner, game_id, None, false).await?;
+            }
+
+            Operation::JoinGamePrivate { game_id, passcode } => {
+                Self::join_game(&runtime, owner, game_id, Some(passcode), false).await?;
+            }
+
+            Operation::MakeMove { game_id, row, col } => {
+                Self::play_move(&runtime, game_id, owner, row, col).await?;
+            }
+
+            Operation::MakeMoveNotation { game_id, cell } => {
+                let state = runtime.state_mut().await;
+                let Some(game) = state
+                    .games
+                    .get(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    return Err(GameError::GameNotFound);
+                };
+                let (row, col) = parse_cell(&cell, game.size)?;
+                drop(state);
+
+                Self::play_move(&runtime, game_id, owner, row, col).await?;
+            }
+
+            Operation::MakeMoves { game_id, moves } => {
                 let mut state = runtime.state_mut().await;
-                
-                if let Some(game) = state.games.get_mut(&game_id) {
-                    match game.join(owner) {
-                        Ok(()) => {
-                            // Send cross-chain message about player joining
-                            runtime
-                                .prepare_message(Message::PlayerJoined {
-                                    game_id,
-                                    player: owner,
-                                })
-                                .send_to_subscribers();
 
-                            log::info!("Player {:?} joined game {}", owner, game_id);
-                        }
-                        Err(e) => {
-                            log::error!("Failed to join game {}: {}", game_id, e);
-                            panic!("Failed to join game: {}", e);
-                        }
+                if let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                {
+                    game.validate()?;
+                    let applied = game.make_moves(&owner, &moves, runtime.system_time())?;
+                    // Send cross-chain messages about each move
+                    for &(player, row, col) in &applied {
+                        runtime
+                            .prepare_message(Message::MoveMade {
+                                game_id,
+                                player,
+                                row,
+                                col,
+                            })
+                            .send_to_subscribers();
+                    }
+
+                    let result = format!("applied:{} requested:{}", applied.len(), moves.len());
+                    Self::log_event(
+                        log::Level::Info,
+                        "MovesApplied",
+                        Some(game_id),
+                        Some(owner),
+                        &result,
+                    );
+                    if !applied.is_empty() {
+                        Self::notify_turn(&runtime, game_id, game);
                     }
                 } else {
-                    panic!("Game {} not found", game_id);
+                    return Err(GameError::GameNotFound);
                 }
+
+                drop(state);
+                Self::settle_game(&runtime, game_id).await;
             }
 
-            Operation::MakeMove { game_id, row, col } => {
+            Operation::CreateGameVsBot { difficulty } => {
+                let game = Game::new_vs_bot(
+                    owner,
+                    Self::bot_owner(&runtime),
+                    difficulty,
+                    runtime.chain_id(),
+                    runtime.system_time(),
+                )
+                .expect("The bot cannot be the creator");
+                Self::create_game(&runtime, owner, game).await?;
+            }
+
+            Operation::PlayBot { game_id } => {
+                let bot = Self::bot_owner(&runtime);
+                let state = runtime.state_mut().await;
+
+                let Some(game) = state
+                    .games
+                    .get(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    return Err(GameError::GameNotFound);
+                };
+                let (row, col) = game.bot_move(&bot)?;
+                drop(state);
+
+                Self::play_move(&runtime, game_id, bot, row, col).await?;
+            }
+
+            Operation::ResignGame { game_id } => {
                 let mut state = runtime.state_mut().await;
-                
-                if let Some(game) = state.games.get_mut(&game_id) {
-                    match game.make_move(&owner, row, col) {
-                        Ok(()) => {
-                            // Send cross-chain message about move
-                            runtime
-                                .prepare_message(Message::MoveMade {
-                                    game_id,
-                                    player: owner,
-                                    row,
-                                    col,
-                                })
-                                .send_to_subscribers();
 
-                            log::info!(
-                                "Player {:?} made move at ({}, {}) in game {}",
-                                owner, row, col, game_id
-                            );
+                if let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                {
+                    game.resign(&owner, runtime.system_time())?;
+                    Self::log_event(
+                        log::Level::Info,
+                        "ResignPending",
+                        Some(game_id),
+                        Some(owner),
+                        "ok",
+                    );
+                } else {
+                    return Err(GameError::GameNotFound);
+                }
+            }
 
-                            // Check if game ended
-                            match &game.status {
-                                GameStatus::Won(winner) => {
-                                    log::info!("Game {} won by {:?}!", game_id, winner);
-                                }
-                                GameStatus::Draw => {
-                                    log::info!("Game {} ended in a draw!", game_id);
-                                }
-                                _ => {}
-                            }
-                        }
-                        Err(e) => {
-                            log::error!("Failed to make move in game {}: {}", game_id, e);
-                            panic!("Failed to make move: {}", e);
+            Operation::FinalizeResign { game_id } => {
+                let mut state = runtime.state_mut().await;
+
+                if let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                {
+                    let resigner = game.finalize_resign(runtime.system_time())?;
+                    let resigner = game
+                        .account_for(resigner)
+                        .expect("Resigning player must be seated");
+
+                    // Send cross-chain message about how the resignation ended the game
+                    if matches!(game.status, GameStatus::Draw(_)) {
+                        runtime
+                            .prepare_message(Message::DrawAgreed { game_id })
+                            .send_to_subscribers();
+
+                        Self::log_event(
+                            log::Level::Info,
+                            "GameResigned",
+                            Some(game_id),
+                            Some(resigner),
+                            "draw:no_moves",
+                        );
+                    } else {
+                        runtime
+                            .prepare_message(Message::GameResigned { game_id, resigner })
+                            .send_to_subscribers();
+
+                        Self::log_event(
+                            log::Level::Info,
+                            "GameResigned",
+                            Some(game_id),
+                            Some(resigner),
+                            "ok",
+                        );
+                    }
+                } else {
+                    return Err(GameError::GameNotFound);
+                }
+
+                drop(state);
+                Self::settle_game(&runtime, game_id).await;
+            }
+
+            Operation::OfferDraw { game_id } => {
+                let mut state = runtime.state_mut().await;
+
+                if let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                {
+                    game.offer_draw(&owner, runtime.system_time())?;
+                    Self::log_event(
+                        log::Level::Info,
+                        "DrawOffered",
+                        Some(game_id),
+                        Some(owner),
+                        "ok",
+                    );
+                } else {
+                    return Err(GameError::GameNotFound);
+                }
+            }
+
+            Operation::RespondDraw { game_id, accept } => {
+                let mut state = runtime.state_mut().await;
+
+                if let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                {
+                    game.respond_draw(&owner, accept, runtime.system_time())?;
+                    if accept {
+                        // Send cross-chain message about the agreed draw
+                        runtime
+                            .prepare_message(Message::DrawAgreed { game_id })
+                            .send_to_subscribers();
+
+                        Self::log_event(
+                            log::Level::Info,
+                            "DrawAgreed",
+                            Some(game_id),
+                            Some(owner),
+                            "ok",
+                        );
+                    } else {
+                        Self::log_event(
+                            log::Level::Info,
+                            "DrawDeclined",
+                            Some(game_id),
+                            Some(owner),
+                            "ok",
+                        );
+                    }
+                } else {
+                    return Err(GameError::GameNotFound);
+                }
+
+                drop(state);
+                Self::settle_game(&runtime, game_id).await;
+            }
+
+            Operation::RequestUndo { game_id } => {
+                let mut state = runtime.state_mut().await;
+
+                if let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                {
+                    game.request_undo(&owner, runtime.system_time())?;
+                    Self::log_event(
+                        log::Level::Info,
+                        "UndoRequested",
+                        Some(game_id),
+                        Some(owner),
+                        "ok",
+                    );
+                } else {
+                    return Err(GameError::GameNotFound);
+                }
+            }
+
+            Operation::RespondUndo { game_id, accept } => {
+                let mut state = runtime.state_mut().await;
+
+                if let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                {
+                    let last_move = game.moves.last().copied();
+                    game.respond_undo(&owner, accept, runtime.system_time())?;
+                    if let (true, Some((_, row, col))) = (accept, last_move) {
+                        // Send cross-chain message so mirrors take back the move too
+                        runtime
+                            .prepare_message(Message::MoveUndone { game_id, row, col })
+                            .send_to_subscribers();
+
+                        let result = format!("cell:({}, {})", row, col);
+                        Self::log_event(
+                            log::Level::Info,
+                            "MoveUndone",
+                            Some(game_id),
+                            Some(owner),
+                            &result,
+                        );
+                    } else {
+                        Self::log_event(
+                            log::Level::Info,
+                            "UndoDeclined",
+                            Some(game_id),
+                            Some(owner),
+                            "ok",
+                        );
+                    }
+                } else {
+                    return Err(GameError::GameNotFound);
+                }
+            }
+
+            Operation::RequestRematch { game_id } => {
+                let mut state = runtime.state_mut().await;
+                let now = runtime.system_time();
+
+                // Work on a copy, so nothing is stored until every check has passed
+                let Some(mut game) = state
+                    .games
+                    .get(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    return Err(GameError::GameNotFound);
+                };
+                let rematch = if game.request_rematch(&owner, now)? {
+                    Some(game.rematch(now)?)
+                } else {
+                    None
+                };
+                let reserved = match &rematch {
+                    Some(new_game) => {
+                        let player_o = new_game.player_o.expect("Rematches are fully seated");
+                        for player in [new_game.player_x, player_o] {
+                            state.check_active_limit(&player, MAX_ACTIVE_GAMES).await?;
                         }
+                        let reservation = state
+                            .reserve_game_ids(new_game.chain_id, &[new_game.player_x])
+                            .await?;
+                        let max_games = runtime.application_parameters().max_games;
+                        Some((reservation, state.game_to_evict(max_games).await?))
+                    }
+                    None => None,
+                };
+
+                state
+                    .games
+                    .insert(&game_id, game)
+                    .expect("Failed to store game");
+                Self::log_event(
+                    log::Level::Info,
+                    "RematchRequested",
+                    Some(game_id),
+                    Some(owner),
+                    "ok",
+                );
+
+                if let (Some(new_game), Some((mut reservation, evicted))) = (rematch, reserved) {
+                    if let Some(evicted) = evicted {
+                        Self::evict_game(&mut state, evicted);
+                    }
+                    let new_game_id = state.insert_reserved_game(&mut reservation, new_game);
+
+                    // Send cross-chain message about the rematch
+                    runtime
+                        .prepare_message(Message::RematchStarted {
+                            old_game_id: game_id,
+                            new_game_id,
+                        })
+                        .send_to_subscribers();
+
+                    let result = format!("rematch_of:{}", game_id);
+                    Self::log_event(
+                        log::Level::Info,
+                        "RematchStarted",
+                        Some(new_game_id),
+                        Some(owner),
+                        &result,
+                    );
+                }
+            }
+
+            Operation::ClaimTimeoutWin { game_id } => {
+                let mut state = runtime.state_mut().await;
+
+                if let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                {
+                    game.claim_timeout_win(&owner, runtime.system_time())?;
+                    Self::log_event(
+                        log::Level::Info,
+                        "TimeoutClaimed",
+                        Some(game_id),
+                        Some(owner),
+                        "won",
+                    );
+                } else {
+                    return Err(GameError::GameNotFound);
+                }
+
+                drop(state);
+                Self::settle_game(&runtime, game_id).await;
+            }
+
+            Operation::ClaimDeadDraw { game_id } => {
+                let mut state = runtime.state_mut().await;
+
+                if let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                {
+                    game.claim_dead_draw(&owner, runtime.system_time())?;
+                    Self::log_event(
+                        log::Level::Info,
+                        "DeadPositionClaimed",
+                        Some(game_id),
+                        Some(owner),
+                        "draw",
+                    );
+                } else {
+                    return Err(GameError::GameNotFound);
+                }
+
+                drop(state);
+                Self::settle_game(&runtime, game_id).await;
+            }
+
+            Operation::AbortGame { game_id } => {
+                Self::abort_game(&runtime, owner, game_id).await?;
+            }
+
+            Operation::TransferSeat { game_id, to } => {
+                let mut state = runtime.state_mut().await;
+
+                if let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                {
+                    let seat = game.transfer_seat(&owner, to, runtime.system_time())?;
+                    // Send cross-chain message about the new seat holder
+                    runtime
+                        .prepare_message(Message::SeatTransferred {
+                            game_id,
+                            from: owner,
+                            to,
+                        })
+                        .send_to_subscribers();
+
+                    let result = format!("seat:{} to:{}", seat.symbol(), to);
+                    Self::log_event(
+                        log::Level::Info,
+                        "SeatTransferred",
+                        Some(game_id),
+                        Some(owner),
+                        &result,
+                    );
+                } else {
+                    return Err(GameError::GameNotFound);
+                }
+            }
+
+            Operation::AdoptWaitingGame { game_id } => {
+                let mut state = runtime.state_mut().await;
+
+                if let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                {
+                    let old = game.adopt(owner, runtime.system_time())?;
+                    // Send cross-chain message about the new creator
+                    runtime
+                        .prepare_message(Message::CreatorReplaced {
+                            game_id,
+                            old,
+                            new: owner,
+                        })
+                        .send_to_subscribers();
+
+                    let result = format!("from:{}", old);
+                    Self::log_event(
+                        log::Level::Info,
+                        "GameAdopted",
+                        Some(game_id),
+                        Some(owner),
+                        &result,
+                    );
+                } else {
+                    return Err(GameError::GameNotFound);
+                }
+            }
+
+            Operation::ResetGame { game_id } => {
+                let mut state = runtime.state_mut().await;
+
+                if let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                {
+                    game.reset(&owner, runtime.system_time())?;
+                    // Send cross-chain message about the reset
+                    runtime
+                        .prepare_message(Message::GameReset { game_id })
+                        .send_to_subscribers();
+
+                    Self::log_event(
+                        log::Level::Info,
+                        "GameReset",
+                        Some(game_id),
+                        Some(owner),
+                        "ok",
+                    );
+                } else {
+                    return Err(GameError::GameNotFound);
+                }
+            }
+
+            Operation::ArchiveGame { game_id } => {
+                Self::archive_game(&runtime, owner, game_id).await?;
+            }
+
+            Operation::DeleteGame { game_id } => {
+                let mut state = runtime.state_mut().await;
+
+                let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    return Err(GameError::GameNotFound);
+                };
+
+                if game.request_delete(&owner, runtime.system_time())? {
+                    state
+                        .games
+                        .remove(&game_id)
+                        .expect("Failed to remove game");
+
+                    // Send cross-chain message so mirrors drop the game too
+                    runtime
+                        .prepare_message(Message::GameDeleted { game_id })
+                        .send_to_subscribers();
+
+                    Self::log_event(
+                        log::Level::Info,
+                        "GameDeleted",
+                        Some(game_id),
+                        Some(owner),
+                        "ok",
+                    );
+                } else {
+                    Self::log_event(
+                        log::Level::Info,
+                        "DeleteRequested",
+                        Some(game_id),
+                        Some(owner),
+                        "ok",
+                    );
+                }
+            }
+
+            Operation::SweepInactive { older_than_secs } => {
+                let mut state = runtime.state_mut().await;
+
+                // Only games hosted on this chain are swept; mirrors follow their host
+                let swept = state
+                    .sweep_inactive(runtime.chain_id(), older_than_secs, runtime.system_time())
+                    .await;
+                drop(state);
+
+                for &game_id in &swept {
+                    Self::settle_game(&runtime, game_id).await;
+                }
+
+                let result = format!("swept:{}", swept.len());
+                Self::log_event(log::Level::Info, "GamesSwept", None, Some(owner), &result);
+            }
+
+            Operation::LeaveGame { game_id } => {
+                let mut state = runtime.state_mut().await;
+
+                if let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                {
+                    // The creator leaving an unjoined game is the same as aborting it
+                    if game.player_x == owner {
+                        drop(state);
+                        return Self::abort_game(&runtime, owner, game_id).await;
+                    }
+
+                    game.leave(&owner, runtime.system_time())?;
+                    // Send cross-chain message about the player leaving
+                    runtime
+                        .prepare_message(Message::PlayerLeft {
+                            game_id,
+                            player: owner,
+                        })
+                        .send_to_subscribers();
+
+                    Self::log_event(
+                        log::Level::Info,
+                        "PlayerLeft",
+                        Some(game_id),
+                        Some(owner),
+                        "ok",
+                    );
+                } else {
+                    return Err(GameError::GameNotFound);
+                }
+            }
+
+            Operation::Spectate { game_id } => {
+                let mut state = runtime.state_mut().await;
+
+                if let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                {
+                    if game.spectate(owner, runtime.system_time())? {
+                        // Send cross-chain message about the new spectator
+                        runtime
+                            .prepare_message(Message::SpectatorJoined {
+                                game_id,
+                                spectator: owner,
+                            })
+                            .send_to_subscribers();
+
+                        Self::log_event(
+                            log::Level::Info,
+                            "SpectatorAdded",
+                            Some(game_id),
+                            Some(owner),
+                            "ok",
+                        );
+                    } else {
+                        Self::log_event(
+                            log::Level::Info,
+                            "SpectatorAdded",
+                            Some(game_id),
+                            Some(owner),
+                            "already_spectating",
+                        );
                     }
                 } else {
-                    panic!("Game {} not found", game_id);
+                    return Err(GameError::GameNotFound);
+                }
+            }
+
+            Operation::SetDisplayName { name } => {
+                validate_display_name(&name)?;
+
+                let mut state = runtime.state_mut().await;
+                state
+                    .display_names
+                    .insert(&owner, name.clone())
+                    .expect("Failed to store display name");
+
+                let result = format!("name:{:?}", name);
+                Self::log_event(log::Level::Info, "DisplayNameSet", None, Some(owner), &result);
+            }
+
+            Operation::CreateSeries { opponent, best_of } => {
+                let mut series = Series::new(owner, opponent, best_of)?;
+                let (player_x, player_o) = series.next_seats();
+
+                let state = runtime.state_mut().await;
+                // The creator's own limit is checked when the first game is created
+                state.check_active_limit(&opponent, MAX_ACTIVE_GAMES).await?;
+                let series_id = *state.next_series_id.get();
+                let next_series_id = series_id
+                    .checked_add(1)
+                    .ok_or(GameError::IdSpaceExhausted)?;
+                drop(state);
+
+                let mut game = Game::new_between(
+                    player_x,
+                    player_o,
+                    runtime.chain_id(),
+                    runtime.system_time(),
+                )
+                .expect("Series players must differ");
+                game.series_id = Some(series_id);
+                // Creating the game runs the last checks, so the series is only stored after it
+                let game_id = Self::create_game(&runtime, owner, game).await?;
+
+                // Send cross-chain message about the opponent taking their seat
+                runtime
+                    .prepare_message(Message::PlayerJoined {
+                        game_id,
+                        player: opponent,
+                    })
+                    .send_to_subscribers();
+
+                series.game_ids.push(game_id);
+                let mut state = runtime.state_mut().await;
+                state.next_series_id.set(next_series_id);
+                state
+                    .series
+                    .insert(&series_id, series)
+                    .expect("Failed to store series");
+
+                let result = format!(
+                    "series:{} opponent:{} best_of:{}",
+                    series_id, opponent, best_of
+                );
+                Self::log_event(
+                    log::Level::Info,
+                    "SeriesCreated",
+                    Some(game_id),
+                    Some(owner),
+                    &result,
+                );
+            }
+
+            Operation::WithdrawFromSeries { series_id } => {
+                let mut state = runtime.state_mut().await;
+                let Some(mut series) = state
+                    .series
+                    .get(&series_id)
+                    .await
+                    .expect("Failed to load series")
+                else {
+                    return Err(GameError::SeriesNotFound);
+                };
+                let winner = series.withdraw(&owner)?;
+                state
+                    .series
+                    .insert(&series_id, series)
+                    .expect("Failed to store series");
+
+                // Send cross-chain message about the withdrawal
+                runtime
+                    .prepare_message(Message::SeriesWithdrawn {
+                        series_id,
+                        player: owner,
+                    })
+                    .send_to_subscribers();
+
+                let result = format!("series:{} winner:{}", series_id, winner);
+                Self::log_event(log::Level::Info, "SeriesWithdrawn", None, Some(owner), &result);
+            }
+
+            Operation::CreateTournament { players } => {
+                let mut tournament = Tournament::new(players)?;
+
+                let mut state = runtime.state_mut().await;
+                for player in &tournament.players {
+                    state.check_active_limit(player, MAX_ACTIVE_GAMES).await?;
                 }
+                let tournament_id = *state.next_tournament_id.get();
+                let next_tournament_id = tournament_id
+                    .checked_add(1)
+                    .ok_or(GameError::IdSpaceExhausted)?;
+
+                // Nothing is stored unless every game of the first round can start
+                let matches = tournament.pending_matches();
+                Self::start_tournament_games(
+                    &runtime,
+                    &mut state,
+                    tournament_id,
+                    &mut tournament,
+                    &matches,
+                )
+                .await?;
+                state.next_tournament_id.set(next_tournament_id);
+                let result = format!(
+                    "tournament:{} players:{}",
+                    tournament_id, tournament.players.len()
+                );
+                Self::log_event(log::Level::Info, "TournamentCreated", None, Some(owner), &result);
+                state
+                    .tournaments
+                    .insert(&tournament_id, tournament)
+                    .expect("Failed to store tournament");
+            }
+
+            Operation::EnqueueMatch => {
+                let mut state = runtime.state_mut().await;
+                state.check_active_limit(&owner, MAX_ACTIVE_GAMES).await?;
+
+                let Some(game_id) = state.oldest_open_game(&owner, runtime.chain_id()).await else {
+                    drop(state);
+                    let game = Game::new(owner, runtime.chain_id(), runtime.system_time());
+                    let game_id = Self::create_game(&runtime, owner, game).await?;
+                    Self::log_event(
+                        log::Level::Info,
+                        "MatchQueued",
+                        Some(game_id),
+                        Some(owner),
+                        "waiting",
+                    );
+                    return Ok(());
+                };
+
+                drop(state);
+                // A mirrored game is only joined once its host seats the player, which
+                // is also when the pairing is announced
+                Self::join_game(&runtime, owner, game_id, None, true).await?;
+            }
+
+            Operation::CreateFromPosition { code } => {
+                let game = Game::from_position(owner, runtime.chain_id(), &code, runtime.system_time())?;
+                Self::create_game(&runtime, owner, game).await?;
             }
+
+            Operation::ImportGame { data } => {
+                let export = serde_json::from_str::<GameExport>(&data)
+                    .map_err(|e| GameError::InvalidImport(e.to_string()))?;
+                let game = export.into_game(runtime.chain_id())?;
+                if game.player_for(&owner).is_none() {
+                    return Err(GameError::NotAParticipant);
+                }
+                Self::create_game(&runtime, owner, game).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Contract for TicTacToeContract {
+    type Message = Message;
+    type Parameters = TicTacToeParameters;
+    type State = TicTacToeState;
+
+    async fn load(runtime: ContractRuntime<Self>) -> Self {
+        TicTacToeContract
+    }
+
+    async fn instantiate(&mut self, _runtime: ContractRuntime<Self>, _argument: ()) {}
+
+    async fn execute_operation(
+        &mut self,
+        runtime: ContractRuntime<Self>,
+        operation: Operation,
+    ) -> Self::Response {
+        let owner = runtime
+            .authenticated_signer()
+            .expect("Missing authentication");
+
+        // Each handler logs what it did, so only failures are logged here
+        let (event, game_id) = Self::describe_operation(&operation);
+        let result = Self::handle_operation(runtime, owner, operation).await;
+        if let Err(e) = &result {
+            let result = format!("error:{:?}", e.to_string());
+            Self::log_event(log::Level::Error, event, game_id, Some(owner), &result);
         }
+        result
     }
 
     async fn execute_message(
@@ -133,21 +1805,727 @@ impl Contract for TicTacToeContract {
         runtime: ContractRuntime<Self>,
         message: Message,
     ) {
-        log::info!("Received message: {:?}", message);
-        
+        let host_chain = runtime
+            .message_id()
+            .expect("Missing message ID")
+            .chain_id;
+        let event = Self::message_name(&message);
+
+        // Join requests are addressed to this chain as a host, not mirrored from another
+        if runtime.application_parameters().results_only
+            && !matches!(message, Message::RequestJoin { .. })
+        {
+            Self::record_result(&runtime, host_chain, message).await;
+            return;
+        }
+
         match message {
-            Message::GameCreated { game_id, creator } => {
-                log::info!("Game {} was created by {:?}", game_id, creator);
+            Message::GameCreated {
+                game_id,
+                creator,
+                passcode_hash,
+                setup,
+            } => {
+                let mut state = runtime.state_mut().await;
+
+                // Mirror the game unless we already know about it
+                if state
+                    .games
+                    .contains_key(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:duplicate",
+                    );
+                    return;
+                }
+
+                let mut game =
+                    match Game::from_setup(creator, host_chain, &setup, runtime.system_time()) {
+                        Ok(game) => game,
+                        Err(e) => {
+                            let result = format!("ignored:{:?}", e.to_string());
+                            Self::log_event(
+                                log::Level::Warn,
+                                event,
+                                Some(game_id),
+                                Some(creator),
+                                &result,
+                            );
+                            return;
+                        }
+                    };
+                game.passcode_hash = passcode_hash;
+                // Mirrors count towards the limit too, but never push out a game in progress
+                let max_games = runtime.application_parameters().max_games;
+                match state.game_to_evict(max_games).await {
+                    Ok(Some(evicted)) => Self::evict_game(&mut state, evicted),
+                    Ok(None) => {}
+                    Err(e) => {
+                        let result = format!("ignored:{:?}", e.to_string());
+                        Self::log_event(
+                            log::Level::Warn,
+                            event,
+                            Some(game_id),
+                            Some(creator),
+                            &result,
+                        );
+                        return;
+                    }
+                }
+                state
+                    .games
+                    .insert(&game_id, game)
+                    .expect("Failed to store game");
+
+                Self::log_event(log::Level::Info, event, Some(game_id), Some(creator), "mirrored");
+            }
+            Message::RequestJoin {
+                game_id,
+                player,
+                passcode,
+                matched,
+            } => {
+                // Here the sender is the joiner's chain rather than the host
+                let joiner_chain = host_chain;
+                if runtime.authenticated_signer() != Some(player) {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:unauthenticated",
+                    );
+                    return;
+                }
+
+                let mut state = runtime.state_mut().await;
+                if let Err(e) = state.check_active_limit(&player, MAX_ACTIVE_GAMES).await {
+                    let result = format!("refused:{:?}", e.to_string());
+                    Self::log_event(log::Level::Warn, event, Some(game_id), Some(player), &result);
+                    return;
+                }
+                let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:unknown_game",
+                    );
+                    return;
+                };
+                if game.chain_id != runtime.chain_id() {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:hosted_elsewhere",
+                    );
+                    return;
+                }
+
+                match game.join(player, joiner_chain, passcode.as_deref(), runtime.system_time()) {
+                    Ok(()) => {
+                        // Tell the subscribers and the joiner, who may not be subscribed
+                        runtime
+                            .prepare_message(Message::PlayerJoined { game_id, player })
+                            .send_to_subscribers();
+                        runtime
+                            .prepare_message(Message::PlayerJoined { game_id, player })
+                            .send_to(joiner_chain);
+                        if matched {
+                            let player_x = game.player_x;
+                            runtime
+                                .prepare_message(Message::Matched {
+                                    game_id,
+                                    player_x,
+                                    player_o: player,
+                                })
+                                .send_to_subscribers();
+                            runtime
+                                .prepare_message(Message::Matched {
+                                    game_id,
+                                    player_x,
+                                    player_o: player,
+                                })
+                                .send_to(joiner_chain);
+                        }
+
+                        Self::log_event(
+                            log::Level::Info,
+                            event,
+                            Some(game_id),
+                            Some(player),
+                            "joined",
+                        );
+                    }
+                    Err(e) => {
+                        let result = format!("refused:{:?}", e.to_string());
+                        Self::log_event(
+                            log::Level::Warn,
+                            event,
+                            Some(game_id),
+                            Some(player),
+                            &result,
+                        );
+                    }
+                }
             }
             Message::PlayerJoined { game_id, player } => {
-                log::info!("Player {:?} joined game {}", player, game_id);
+                let mut state = runtime.state_mut().await;
+
+                let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:unknown_game",
+                    );
+                    return;
+                };
+                // Games hosted here are only joined through `RequestJoin`, which runs the
+                // full join validation; this message merely mirrors a join the host accepted
+                if game.chain_id != host_chain {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:not_host",
+                    );
+                    return;
+                }
+
+                match game.seat_player_o(player, runtime.system_time()) {
+                    Ok(()) => {
+                        Self::log_event(
+                            log::Level::Info,
+                            event,
+                            Some(game_id),
+                            Some(player),
+                            "joined",
+                        );
+                    }
+                    Err(e) => {
+                        let result = format!("ignored:{:?}", e.to_string());
+                        Self::log_event(
+                            log::Level::Warn,
+                            event,
+                            Some(game_id),
+                            Some(player),
+                            &result,
+                        );
+                    }
+                }
             }
             Message::MoveMade { game_id, player, row, col } => {
-                log::info!(
-                    "Player {:?} made move at ({}, {}) in game {}",
-                    player, row, col, game_id
+                let mut state = runtime.state_mut().await;
+
+                // Only games announced by `GameCreated` are mirrored, since a move alone
+                // says nothing about the board or the seats
+                let Some(mut game) = state
+                    .games
+                    .get(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:unknown_game",
+                    );
+                    return;
+                };
+
+                if game.chain_id != host_chain {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:not_host",
+                    );
+                    return;
+                }
+
+                // A late move for a game that has already been decided is refused here too
+                match game.apply_remote_move(player, row, col, runtime.system_time()) {
+                    Ok(()) => {
+                        state
+                            .games
+                            .insert(&game_id, game)
+                            .expect("Failed to store game");
+
+                        let result = format!("cell:({}, {})", row, col);
+                        Self::log_event(
+                            log::Level::Info,
+                            event,
+                            Some(game_id),
+                            Some(player),
+                            &result,
+                        );
+                    }
+                    Err(e) => {
+                        let result = format!("ignored:{:?}", e.to_string());
+                        Self::log_event(
+                            log::Level::Warn,
+                            event,
+                            Some(game_id),
+                            Some(player),
+                            &result,
+                        );
+                    }
+                }
+            }
+            Message::MoveUndone { game_id, row, col } => {
+                let mut state = runtime.state_mut().await;
+
+                let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:unknown_game",
+                    );
+                    return;
+                };
+                if game.chain_id != host_chain {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:not_host",
+                    );
+                    return;
+                }
+
+                match game.apply_remote_undo(row, col, runtime.system_time()) {
+                    Ok(()) => {
+                        let result = format!("cell:({}, {})", row, col);
+                        Self::log_event(log::Level::Info, event, Some(game_id), None, &result);
+                    }
+                    Err(e) => {
+                        let result = format!("ignored:{:?}", e.to_string());
+                        Self::log_event(log::Level::Warn, event, Some(game_id), None, &result);
+                    }
+                }
+            }
+            Message::GameEnded { game_id, result, .. } => {
+                let mut state = runtime.state_mut().await;
+
+                let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:unknown_game",
+                    );
+                    return;
+                };
+                if game.chain_id != host_chain {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:not_host",
+                    );
+                    return;
+                }
+
+                game.apply_remote_result(result, runtime.system_time());
+                let result = format!("{:?}", result);
+                Self::log_event(log::Level::Info, event, Some(game_id), None, &result);
+            }
+            Message::GameForfeited {
+                game_id,
+                offender,
+                reason,
+            } => {
+                let result = format!("forfeited:{:?}", reason);
+                Self::log_event(log::Level::Info, event, Some(game_id), Some(offender), &result);
+            }
+            Message::GameResigned { game_id, resigner } => {
+                let mut state = runtime.state_mut().await;
+
+                let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:unknown_game",
+                    );
+                    return;
+                };
+                if game.chain_id != host_chain {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:not_host",
+                    );
+                    return;
+                }
+                let Some(seat) = game.player_for(&resigner) else {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:not_a_player",
+                    );
+                    return;
+                };
+
+                game.apply_remote_result(GameResult::WonBy(seat.opponent()), runtime.system_time());
+                Self::log_event(
+                    log::Level::Info,
+                    event,
+                    Some(game_id),
+                    Some(resigner),
+                    "resigned",
+                );
+            }
+            Message::DrawAgreed { game_id } => {
+                let mut state = runtime.state_mut().await;
+
+                let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:unknown_game",
+                    );
+                    return;
+                };
+                if game.chain_id != host_chain {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:not_host",
+                    );
+                    return;
+                }
+
+                game.apply_remote_result(GameResult::Draw(DrawReason::Agreement), runtime.system_time());
+                Self::log_event(log::Level::Info, event, Some(game_id), None, "draw");
+            }
+            Message::RematchStarted { old_game_id, new_game_id } => {
+                let result = format!("rematch_of:{}", old_game_id);
+                Self::log_event(log::Level::Info, event, Some(new_game_id), None, &result);
+            }
+            Message::SeatTransferred { game_id, from, to } => {
+                let mut state = runtime.state_mut().await;
+
+                let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:unknown_game",
+                    );
+                    return;
+                };
+                if game.chain_id != host_chain {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:not_host",
+                    );
+                    return;
+                }
+                if !game.replace_seat(&from, to, runtime.system_time()) {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        Some(from),
+                        "ignored:not_a_player",
+                    );
+                    return;
+                }
+                let result = format!("to:{}", to);
+                Self::log_event(log::Level::Info, event, Some(game_id), Some(from), &result);
+            }
+            Message::CreatorReplaced { game_id, old, new } => {
+                let mut state = runtime.state_mut().await;
+
+                let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:unknown_game",
+                    );
+                    return;
+                };
+                if game.chain_id != host_chain {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:not_host",
+                    );
+                    return;
+                }
+                if game.player_x != old {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        Some(old),
+                        "ignored:stale_creator",
+                    );
+                    return;
+                }
+
+                game.player_x = new;
+                game.updated_at = runtime.system_time();
+                let result = format!("from:{}", old);
+                Self::log_event(log::Level::Info, event, Some(game_id), Some(new), &result);
+            }
+            Message::GameReset { game_id } => {
+                let mut state = runtime.state_mut().await;
+
+                let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:unknown_game",
+                    );
+                    return;
+                };
+                if game.chain_id != host_chain {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:not_host",
+                    );
+                    return;
+                }
+
+                game.apply_remote_reset(runtime.system_time());
+                Self::log_event(log::Level::Info, event, Some(game_id), None, "reset");
+            }
+            Message::GameDeleted { game_id } => {
+                let mut state = runtime.state_mut().await;
+
+                let Some(game) = state
+                    .games
+                    .get(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:unknown_game",
+                    );
+                    return;
+                };
+                if game.chain_id != host_chain {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:not_host",
+                    );
+                    return;
+                }
+
+                state
+                    .games
+                    .remove(&game_id)
+                    .expect("Failed to remove game");
+                Self::log_event(log::Level::Info, event, Some(game_id), None, "deleted");
+            }
+            Message::SeriesWithdrawn { series_id, player } => {
+                let result = format!("series:{}", series_id);
+                Self::log_event(log::Level::Info, event, None, Some(player), &result);
+            }
+            Message::YourTurn { game_id, player } => {
+                Self::log_event(log::Level::Info, event, Some(game_id), Some(player), "your_turn");
+            }
+            Message::GameAborted { game_id } => {
+                let mut state = runtime.state_mut().await;
+
+                let Some(game) = state
+                    .games
+                    .get(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:unknown_game",
+                    );
+                    return;
+                };
+                if game.chain_id != host_chain {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:not_host",
+                    );
+                    return;
+                }
+
+                // The host has removed the game, so the mirror goes too
+                state
+                    .games
+                    .remove(&game_id)
+                    .expect("Failed to remove game");
+                Self::log_event(log::Level::Info, event, Some(game_id), None, "removed");
+            }
+            Message::PlayerLeft { game_id, player } => {
+                let mut state = runtime.state_mut().await;
+
+                let Some(game) = state
+                    .games
+                    .get_mut(&game_id)
+                    .await
+                    .expect("Failed to load game")
+                else {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:unknown_game",
+                    );
+                    return;
+                };
+                if game.chain_id != host_chain {
+                    Self::log_event(
+                        log::Level::Warn,
+                        event,
+                        Some(game_id),
+                        None,
+                        "ignored:not_host",
+                    );
+                    return;
+                }
+
+                // Reopen the mirror for a new player O, as leaving did on the host
+                match game.leave(&player, runtime.system_time()) {
+                    Ok(()) => {
+                        Self::log_event(
+                            log::Level::Info,
+                            event,
+                            Some(game_id),
+                            Some(player),
+                            "left",
+                        );
+                    }
+                    Err(e) => {
+                        let result = format!("ignored:{:?}", e.to_string());
+                        Self::log_event(
+                            log::Level::Warn,
+                            event,
+                            Some(game_id),
+                            Some(player),
+                            &result,
+                        );
+                    }
+                }
+            }
+            Message::SpectatorJoined { game_id, spectator } => {
+                Self::log_event(
+                    log::Level::Info,
+                    event,
+                    Some(game_id),
+                    Some(spectator),
+                    "spectating",
                 );
             }
+            Message::Matched {
+                game_id,
+                player_x,
+                player_o,
+            } => {
+                let result = format!("x:{} o:{}", player_x, player_o);
+                Self::log_event(log::Level::Info, event, Some(game_id), None, &result);
+            }
         }
     }
 }
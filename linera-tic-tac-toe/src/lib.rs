@@ -1,27 +1,137 @@
-use async_graphql::{Request, Response, SimpleObject};
+use async_graphql::{ComplexObject, Enum, Request, Response, SimpleObject};
 use linera_sdk::base::{AccountOwner, ChainId};
 use serde::{Deserialize, Serialize};
 
 pub mod state;
 
-use state::{Game, GameStatus, Player, TicTacToeState};
+use state::{
+    perfect_play_result, BotDifficulty, Game, GameError, GameResult, GameSetup, GameStatus,
+    Player, TicTacToeState,
+};
 
 /// Operations that can be executed by the application.
 #[derive(Debug, Deserialize, Serialize)]
 pub enum Operation {
     /// Create a new game
     CreateGame,
-    /// Join an existing game
+    /// Create a new game on a size x size board
+    CreateGameSized { size: usize },
+    /// Create a new game on a size x size board where win_length in a row wins
+    CreateGameCustom {
+        size: usize,
+        win_length: usize,
+        move_deadline_secs: Option<u64>,
+        /// Seat that makes the opening move (X by default)
+        #[serde(default)]
+        first_player: Player,
+        /// Total seconds each player may spend on their moves (no clock by default)
+        #[serde(default)]
+        time_budget_secs: Option<u64>,
+        /// Let an opponent on the creator's own chain join (off by default)
+        #[serde(default)]
+        allow_same_chain: bool,
+        /// Forfeit the game of a player who attempts an illegal move (off by default)
+        #[serde(default)]
+        strict: bool,
+        /// Award the win on resignation even before any move (off by default, which draws instead)
+        #[serde(default)]
+        competitive: bool,
+    },
+    /// Challenge a specific opponent to a new 3x3 game that starts right away
+    CreateGameWithOpponent { opponent: AccountOwner },
+    /// Invite an opponent to a game they can accept or decline
+    ChallengePlayer { opponent: AccountOwner },
+    /// Accept a challenge, starting the game
+    AcceptChallenge { invitation_id: u64 },
+    /// Decline a challenge, or withdraw one the caller issued
+    DeclineChallenge { invitation_id: u64 },
+    /// Create a new 3x3 game with a title and optional description
+    CreateGameTitled {
+        title: String,
+        description: Option<String>,
+    },
+    /// Create a new 3x3 game with handicap stones already on the board
+    CreateGameHandicap { handicap: Vec<(Player, usize, usize)> },
+    /// Join an existing public game
     JoinGame { game_id: u64 },
+    /// Join a private game with its passcode
+    JoinGamePrivate { game_id: u64, passcode: String },
+    /// Create a new 3x3 game that can only be joined with the passcode
+    CreateGamePrivate { passcode: String },
     /// Make a move in a game
     MakeMove { game_id: u64, row: usize, col: usize },
+    /// Make a move in a game, naming the cell like "b2"
+    MakeMoveNotation { game_id: u64, cell: String },
+    /// Make several of your moves in a bot game, the bot replying to each, until one is illegal
+    MakeMoves { game_id: u64, moves: Vec<(usize, usize)> },
+    /// Resign from a game in progress, taking effect once the grace period has passed
+    ResignGame { game_id: u64 },
+    /// End a game whose pending resignation has outlived its grace period
+    FinalizeResign { game_id: u64 },
+    /// Offer a draw to the opponent
+    OfferDraw { game_id: u64 },
+    /// Accept or decline a pending draw offer
+    RespondDraw { game_id: u64, accept: bool },
+    /// Ask the opponent to take back your last move
+    RequestUndo { game_id: u64 },
+    /// Accept or decline a pending undo request
+    RespondUndo { game_id: u64, accept: bool },
+    /// Request a rematch of a finished game with seats swapped
+    RequestRematch { game_id: u64 },
+    /// End a game as a draw once neither player can complete a winning run
+    ClaimDeadDraw { game_id: u64 },
+    /// Claim a win when the opponent has exceeded the move deadline
+    ClaimTimeoutWin { game_id: u64 },
+    /// Abort a game that no other player has joined
+    AbortGame { game_id: u64 },
+    /// Hand the caller's seat in an unfinished game to another account
+    TransferSeat { game_id: u64, to: AccountOwner },
+    /// Take over the creator seat of a waiting game its creator abandoned
+    AdoptWaitingGame { game_id: u64 },
+    /// Clear a stuck game's board and reopen it, as its creator
+    ResetGame { game_id: u64 },
+    /// Move a finished game out of the active games into the archive
+    ArchiveGame { game_id: u64 },
+    /// Agree to delete a finished game, which is removed once both players agree
+    DeleteGame { game_id: u64 },
+    /// End every game on this chain with no move for longer than the threshold
+    SweepInactive { older_than_secs: u64 },
+    /// Leave a game joined by mistake before any move is made
+    LeaveGame { game_id: u64 },
+    /// Watch a game as a spectator
+    Spectate { game_id: u64 },
+    /// Register a display name for the caller
+    SetDisplayName { name: String },
+    /// Create a new game against the built-in bot, which plays O
+    CreateGameVsBot { difficulty: BotDifficulty },
+    /// Have the bot make its move in a game against it
+    PlayBot { game_id: u64 },
+    /// Start a best-of-N series against an opponent
+    CreateSeries { opponent: AccountOwner, best_of: u32 },
+    /// Concede a series to the opponent, leaving the game in progress playable
+    WithdrawFromSeries { series_id: u64 },
+    /// Start a single-elimination tournament between the players, in seeding order
+    CreateTournament { players: Vec<AccountOwner> },
+    /// Join the oldest open game, or create one if none is waiting
+    EnqueueMatch,
+    /// Create a game starting from a position code returned by `position_code`
+    CreateFromPosition { code: String },
+    /// Store an unfinished game exported with `export_game` under a fresh ID
+    ImportGame { data: String },
 }
 
 /// Messages that can be sent across chains.
 #[derive(Debug, Deserialize, Serialize)]
 pub enum Message {
-    /// Notify about a new game created
-    GameCreated { game_id: u64, creator: AccountOwner },
+    /// Notify about a new game created, carrying its globally unique ID
+    GameCreated {
+        game_id: u64,
+        creator: AccountOwner,
+        /// Hash of the passcode needed to join, for private games
+        passcode_hash: Option<[u8; 32]>,
+        /// Board and opening marks, so mirrors play by the same rules as the host
+        setup: GameSetup,
+    },
     /// Notify about a player joining a game
     PlayerJoined { game_id: u64, player: AccountOwner },
     /// Notify about a move made
@@ -31,74 +141,386 @@ pub enum Message {
         row: usize, 
         col: usize 
     },
+    /// Ask the chain hosting a game to seat a player from another chain
+    RequestJoin {
+        game_id: u64,
+        player: AccountOwner,
+        passcode: Option<String>,
+        /// Whether the join comes from the matchmaking queue, to be announced as a pairing
+        #[serde(default)]
+        matched: bool,
+    },
+    /// Notify the player who is to move next that it is their turn
+    YourTurn { game_id: u64, player: AccountOwner },
+    /// Notify about the last move being taken back
+    MoveUndone { game_id: u64, row: usize, col: usize },
+    /// Notify about a player forfeiting a strict game by attempting an illegal move
+    GameForfeited { game_id: u64, offender: AccountOwner, reason: String },
+    /// Notify about the final result of a game that has ended
+    GameEnded {
+        game_id: u64,
+        player_x: AccountOwner,
+        player_o: Option<AccountOwner>,
+        result: GameResult,
+    },
+    /// Notify about a player resigning a game
+    GameResigned { game_id: u64, resigner: AccountOwner },
+    /// Notify about both players agreeing to a draw
+    DrawAgreed { game_id: u64 },
+    /// Notify about a rematch started after both players requested it
+    RematchStarted { old_game_id: u64, new_game_id: u64 },
+    /// Notify about a player conceding a series
+    SeriesWithdrawn { series_id: u64, player: AccountOwner },
+    /// Notify about a game aborted before a second player joined
+    GameAborted { game_id: u64 },
+    /// Notify about a player handing their seat to another account
+    SeatTransferred { game_id: u64, from: AccountOwner, to: AccountOwner },
+    /// Notify about a new player adopting an abandoned waiting game
+    CreatorReplaced { game_id: u64, old: AccountOwner, new: AccountOwner },
+    /// Notify about a game being cleared and reopened by its creator
+    GameReset { game_id: u64 },
+    /// Notify about a finished game being deleted by both players
+    GameDeleted { game_id: u64 },
+    /// Notify about player O leaving a game before any move was made
+    PlayerLeft { game_id: u64, player: AccountOwner },
+    /// Notify about a spectator starting to watch a game
+    SpectatorJoined { game_id: u64, spectator: AccountOwner },
+    /// Notify about two players paired by matchmaking
+    Matched {
+        game_id: u64,
+        player_x: AccountOwner,
+        player_o: AccountOwner,
+    },
 }
 
 /// GraphQL-compatible game representation
 #[derive(SimpleObject)]
+#[graphql(complex)]
 pub struct GameView {
     pub id: u64,
     pub player_x: String,
     pub player_o: Option<String>,
+    pub player_x_name: Option<String>,
+    pub player_o_name: Option<String>,
+    /// Always `size` rows of `size` cells each
     pub board: Vec<Vec<Option<String>>>,
+    pub size: u64,
+    pub win_length: u64,
+    /// Legacy symbol of the player to move; prefer `current_player_kind`
     pub current_player: String,
+    /// Legacy human-readable status; prefer `status_kind`
     pub status: String,
+    pub current_player_kind: PlayerKind,
+    pub status_kind: GameStatusKind,
     pub chain_id: String,
+    pub draw_offer_pending: bool,
+    pub draw_offered_by: Option<String>,
+    pub undo_requested_by: Option<String>,
+    pub pending_resign_by: Option<String>,
+    /// Microseconds since the epoch when the pending resignation was made
+    pub pending_resign_at: Option<u64>,
+    pub winning_line: Option<Vec<Vec<i32>>>,
+    pub move_deadline_secs: u64,
+    pub first_player: String,
+    pub strict: bool,
+    pub competitive: bool,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Whether joining requires a passcode
+    pub private: bool,
+    /// Microseconds left on player X's clock, if the game is played on a clock
+    pub x_time_remaining: Option<u64>,
+    /// Microseconds left on player O's clock, if the game is played on a clock
+    pub o_time_remaining: Option<u64>,
+    /// Microseconds since the epoch when the game started or the last move was made
+    pub last_move_time: u64,
+    /// Seconds since the last move, filled in by the service from its clock
+    pub move_elapsed_secs: Option<u64>,
+    /// Microseconds since the epoch when a draw may be offered again after one was declined,
+    /// filled in by the service from its clock
+    pub next_draw_offer_at: Option<u64>,
+    pub spectator_count: u64,
+    pub move_count: u32,
+    pub is_full: bool,
+    /// Microseconds since the epoch when the game was created
+    pub created_at: u64,
+    /// Microseconds since the epoch when the game last changed
+    pub updated_at: u64,
+    /// Whether X and O have been swapped for viewing from the other side, in which
+    /// case `player_x` is the account that actually plays O and vice versa
+    pub colors_swapped: bool,
+    /// The game the view was built from, kept for fields that are costly to compute
+    #[graphql(skip)]
+    game: Game,
+}
+
+#[ComplexObject]
+impl GameView {
+    /// How the game ends with perfect play, for games in progress
+    ///
+    /// This searches the game tree, so it is only computed when asked for.
+    async fn win_estimate(&self) -> Option<WinEstimate> {
+        WinEstimate::for_game(&self.game)
+    }
 }
 
 impl From<(u64, &Game)> for GameView {
     fn from((id, game): (u64, &Game)) -> Self {
-        let board = game.board
-            .iter()
-            .map(|row| {
-                row.iter()
-                    .map(|cell| cell.map(|p| match p {
-                        Player::X => "X".to_string(),
-                        Player::O => "O".to_string(),
-                    }))
-                    .collect()
-            })
-            .collect();
-
-        let status = match &game.status {
-            GameStatus::WaitingForPlayer => "Waiting for player".to_string(),
-            GameStatus::InProgress => "In progress".to_string(),
-            GameStatus::Won(player) => format!("Won by {}", match player {
-                Player::X => "X",
-                Player::O => "O",
-            }),
-            GameStatus::Draw => "Draw".to_string(),
-        };
+        debug_assert!(game.has_square_board(), "board does not match its size");
+        let board = board_symbols(game);
+        let status = status_label(&game.status);
+        // A resignation only matters while the game it would end is still being played
+        let pending_resign = game
+            .pending_resign
+            .filter(|_| game.status == GameStatus::InProgress);
 
         Self {
             id,
-            player_x: format!("{:?}", game.player_x),
-            player_o: game.player_o.as_ref().map(|p| format!("{:?}", p)),
+            player_x: game.player_x.to_string(),
+            player_o: game.player_o.as_ref().map(|p| p.to_string()),
+            player_x_name: None,
+            player_o_name: None,
             board,
-            current_player: match game.current_player {
-                Player::X => "X".to_string(),
-                Player::O => "O".to_string(),
-            },
+            size: game.size as u64,
+            win_length: game.win_length as u64,
+            current_player: game.current_player.symbol().to_string(),
             status,
-            chain_id: format!("{:?}", game.chain_id),
+            current_player_kind: game.current_player.into(),
+            status_kind: (&game.status).into(),
+            chain_id: game.chain_id.to_string(),
+            draw_offer_pending: game.draw_offer.is_some(),
+            draw_offered_by: game.draw_offer.map(|p| p.symbol().to_string()),
+            undo_requested_by: game.undo_request.map(|p| p.symbol().to_string()),
+            pending_resign_by: pending_resign.map(|(p, _)| p.symbol().to_string()),
+            pending_resign_at: pending_resign.map(|(_, at)| at.micros()),
+            winning_line: game.winning_line.as_ref().map(|line| {
+                line.iter()
+                    .map(|&(row, col)| vec![row as i32, col as i32])
+                    .collect()
+            }),
+            move_deadline_secs: game.move_deadline_secs,
+            first_player: game.first_player.symbol().to_string(),
+            strict: game.strict,
+            competitive: game.competitive,
+            title: game.title.clone(),
+            description: game.description.clone(),
+            private: game.is_private(),
+            x_time_remaining: game.x_time_remaining.map(|remaining| remaining.as_micros()),
+            o_time_remaining: game.o_time_remaining.map(|remaining| remaining.as_micros()),
+            last_move_time: game.last_move_time.micros(),
+            move_elapsed_secs: None,
+            next_draw_offer_at: None,
+            spectator_count: game.spectators.len() as u64,
+            move_count: game.move_count,
+            is_full: game.is_board_full(),
+            created_at: game.created_at.micros(),
+            updated_at: game.updated_at.micros(),
+            colors_swapped: false,
+            game: game.clone(),
+        }
+    }
+}
+
+/// Seat of a player, as a GraphQL enum
+#[derive(Clone, Copy, Debug, Enum, PartialEq, Eq)]
+pub enum PlayerKind {
+    X,
+    O,
+}
+
+impl From<Player> for PlayerKind {
+    fn from(player: Player) -> Self {
+        match player {
+            Player::X => PlayerKind::X,
+            Player::O => PlayerKind::O,
+        }
+    }
+}
+
+/// Status of a game with the winner folded in, as a GraphQL enum
+#[derive(Clone, Copy, Debug, Enum, PartialEq, Eq)]
+pub enum GameStatusKind {
+    WaitingForPlayer,
+    InProgress,
+    XWon,
+    OWon,
+    Draw,
+}
+
+impl From<&GameStatus> for GameStatusKind {
+    fn from(status: &GameStatus) -> Self {
+        match status {
+            GameStatus::WaitingForPlayer => GameStatusKind::WaitingForPlayer,
+            GameStatus::InProgress => GameStatusKind::InProgress,
+            GameStatus::Won(Player::X) => GameStatusKind::XWon,
+            GameStatus::Won(Player::O) => GameStatusKind::OWon,
+            GameStatus::Draw(_) => GameStatusKind::Draw,
+        }
+    }
+}
+
+/// Result a position leads to when both sides play perfectly
+#[derive(Clone, Copy, Debug, Enum, PartialEq, Eq)]
+pub enum ForcedOutcome {
+    XWins,
+    OWins,
+    Draw,
+    /// The board is too large to search to the end
+    Unknown,
+}
+
+/// Estimate of how an in-progress game ends
+#[derive(SimpleObject)]
+pub struct WinEstimate {
+    pub outcome: ForcedOutcome,
+}
+
+impl WinEstimate {
+    /// Estimate the outcome of a game in progress
+    fn for_game(game: &Game) -> Option<Self> {
+        if game.status != GameStatus::InProgress {
+            return None;
+        }
+        let outcome = match perfect_play_result(game) {
+            Some(GameResult::WonBy(Player::X)) => ForcedOutcome::XWins,
+            Some(GameResult::WonBy(Player::O)) => ForcedOutcome::OWins,
+            Some(GameResult::Draw(_)) => ForcedOutcome::Draw,
+            None => ForcedOutcome::Unknown,
+        };
+        Some(Self { outcome })
+    }
+}
+
+/// Game representation without any player or chain identities, for public display
+#[derive(SimpleObject)]
+pub struct PublicGameView {
+    pub board: Vec<Vec<Option<String>>>,
+    pub current_player: String,
+    pub status: String,
+    pub move_count: u32,
+}
+
+impl From<&Game> for PublicGameView {
+    fn from(game: &Game) -> Self {
+        Self {
+            board: board_symbols(game),
+            current_player: game.current_player.symbol().to_string(),
+            status: status_label(&game.status),
+            move_count: game.move_count,
         }
     }
 }
 
+/// Get the symbol in each cell of a game's board
+fn board_symbols(game: &Game) -> Vec<Vec<Option<String>>> {
+    game.board
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| cell.map(|p| p.symbol().to_string()))
+                .collect()
+        })
+        .collect()
+}
+
+/// Describe a game status for display
+fn status_label(status: &GameStatus) -> String {
+    match status {
+        GameStatus::WaitingForPlayer => "Waiting for player".to_string(),
+        GameStatus::InProgress => "In progress".to_string(),
+        GameStatus::Won(player) => format!("Won by {}", player.symbol()),
+        GameStatus::Draw(reason) => format!("Draw ({})", reason.label()),
+    }
+}
+
+/// Parameters fixed when the application is created
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct TicTacToeParameters {
+    /// Record only the results of games hosted on other chains instead of mirroring every move
+    #[serde(default)]
+    pub results_only: bool,
+    /// Most games kept in storage before finished ones are evicted, or zero for no limit
+    #[serde(default)]
+    pub max_games: usize,
+}
+
 /// The application ABI.
 pub struct TicTacToeAbi;
 
 impl linera_sdk::abi::ContractAbi for TicTacToeAbi {
     type Operation = Operation;
+    /// User errors are returned rather than aborting the block
+    type Response = Result<(), GameError>;
     type Message = Message;
-    type Parameters = ();
+    type Parameters = TicTacToeParameters;
     type State = TicTacToeState;
 }
 
 impl linera_sdk::abi::ServiceAbi for TicTacToeAbi {
     type Operation = Operation;
     type Message = Message;
-    type Parameters = ();
+    type Parameters = TicTacToeParameters;
     type State = TicTacToeState;
     type Query = Request;
     type QueryResponse = Response;
 }
+
+#[cfg(test)]
+mod tests {
+    use linera_sdk::base::{CryptoHash, Owner, Timestamp};
+
+    use super::*;
+
+    fn account(name: &str) -> AccountOwner {
+        AccountOwner::User(Owner(CryptoHash::test_hash(name)))
+    }
+
+    /// Check that both the stored board and its view are `size` rows of `size` cells
+    fn assert_board_dimensions(game: &Game) {
+        assert!(game.has_square_board(), "board does not match its size");
+        let view = GameView::from((0, game));
+        assert_eq!(view.size, game.size as u64);
+        assert_eq!(view.board.len(), game.size);
+        assert!(view.board.iter().all(|row| row.len() == game.size));
+    }
+
+    #[test]
+    fn player_strings_round_trip_between_views_and_lookups() {
+        let (x, o) = (account("x"), account("o"));
+        let game = Game::new_between(x, o, ChainId::root(0), Timestamp::from(0)).unwrap();
+        let view = GameView::from((0, &game));
+
+        assert_eq!(view.player_x, x.to_string());
+        assert_eq!(view.player_x.parse::<AccountOwner>().ok(), Some(x));
+        assert_eq!(game.player_for_str(&view.player_x), Some(Player::X));
+        let player_o = view.player_o.as_deref().expect("player O has joined");
+        assert_eq!(game.player_for_str(player_o), Some(Player::O));
+        assert_eq!(game.player_for_str(&account("spectator").to_string()), None);
+    }
+
+    #[test]
+    fn views_keep_the_board_dimensions_through_a_game() {
+        let (x, o) = (account("x"), account("o"));
+        for size in [3, 4, 7, 19] {
+            let last = size - 1;
+            let now = Timestamp::from(0);
+            let mut game = Game::new_custom(x, ChainId::root(0), size, size.min(5), now).unwrap();
+            assert_board_dimensions(&game);
+
+            game.seat_player_o(o, now).unwrap();
+            game.make_move(&x, 0, 0, now).unwrap();
+            assert_board_dimensions(&game);
+            game.make_move(&o, last, last, now).unwrap();
+            assert_board_dimensions(&game);
+
+            game.request_undo(&o, now).unwrap();
+            game.respond_undo(&x, true, now).unwrap();
+            assert_board_dimensions(&game);
+
+            game.resign(&x, now).unwrap();
+            let later = Timestamp::from((state::RESIGN_GRACE_SECS + 1) * 1_000_000);
+            game.finalize_resign(later).unwrap();
+            assert_board_dimensions(&game);
+        }
+    }
+}
@@ -1,20 +1,33 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
-mod state;
-
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use async_graphql::{
-    Context, EmptySubscription, Object, Request, Response, Schema, SimpleObject,
+    Context, Enum, Object, Request, Response, Schema, ServerError, SimpleObject, Subscription,
+};
+use futures::{
+    stream::{self, Stream},
+    StreamExt,
 };
 use linera_sdk::{
-    base::WithServiceAbi,
+    base::{AccountOwner, ChainId, Timestamp, WithServiceAbi},
     Service, ServiceRuntime,
 };
-use tic_tac_toe::{GameView, Message, Operation, TicTacToeAbi, TicTacToeState};
+use tic_tac_toe::{
+    state::{self, TicTacToeState},
+    GameView, Message, Operation, PublicGameView, TicTacToeAbi, TicTacToeParameters,
+};
+
+/// Largest number of games returned by a single page
+const MAX_PAGE_SIZE: u64 = 100;
+
+/// Largest number of games fetched by ID in a single query
+const MAX_BATCH_SIZE: usize = 200;
 
 pub struct TicTacToeService {
     state: Arc<TicTacToeState>,
+    now: Timestamp,
+    chain_id: ChainId,
 }
 
 linera_sdk::service!(TicTacToeService);
@@ -24,13 +37,15 @@ impl WithServiceAbi for TicTacToeService {
 }
 
 impl Service for TicTacToeService {
-    type Parameters = ();
+    type Parameters = TicTacToeParameters;
     type State = TicTacToeState;
 
     async fn load(runtime: ServiceRuntime<Self>) -> Self {
         let state = runtime.state().await;
         Self {
             state: Arc::new(state),
+            now: runtime.system_time(),
+            chain_id: runtime.chain_id(),
         }
     }
 
@@ -38,109 +53,831 @@ impl Service for TicTacToeService {
         let schema = Schema::build(
             QueryRoot {
                 state: self.state.clone(),
+                now: self.now,
+                chain_id: self.chain_id,
             },
             MutationRoot {},
-            EmptySubscription,
+            SubscriptionRoot {
+                query: QueryRoot {
+                    state: self.state.clone(),
+                    now: self.now,
+                    chain_id: self.chain_id,
+                },
+            },
         )
         .finish();
-        schema.execute(request).await
+        // `Schema::execute` refuses subscriptions, so every request is run as a stream.
+        // Queries and mutations yield their single response; a subscription yields the
+        // update it has for this block, which is all a single query can return.
+        schema
+            .execute_stream(request)
+            .next()
+            .await
+            .unwrap_or_else(|| {
+                Response::from_errors(vec![ServerError::new(
+                    "Subscription ended without an update",
+                    None,
+                )])
+            })
     }
 }
 
 struct QueryRoot {
     state: Arc<TicTacToeState>,
+    now: Timestamp,
+    /// Chain this service runs on; games hosted elsewhere are local mirrors
+    chain_id: ChainId,
+}
+
+impl QueryRoot {
+    /// Load every game in ID order
+    async fn load_games(&self) -> Vec<(u64, state::Game)> {
+        let mut games = Vec::new();
+        self.state
+            .games
+            .for_each_index_value(|id, game| {
+                games.push((id, game.into_owned()));
+                Ok(())
+            })
+            .await
+            .expect("Failed to load games");
+        games.sort_by_key(|(id, _)| *id);
+        games
+    }
+
+    /// Load every archived game, sorted by ID
+    async fn load_archived_games(&self) -> Vec<(u64, state::Game)> {
+        let mut games = Vec::new();
+        self.state
+            .archived_games
+            .for_each_index_value(|id, game| {
+                games.push((id, game.into_owned()));
+                Ok(())
+            })
+            .await
+            .expect("Failed to load archived games");
+        games.sort_by_key(|(id, _)| *id);
+        games
+    }
+
+    /// Load every live and archived game, so that archiving a game never changes statistics
+    async fn load_all_games(&self) -> Vec<(u64, state::Game)> {
+        let mut games = self.load_games().await;
+        games.extend(self.load_archived_games().await);
+        games.sort_by_key(|(id, _)| *id);
+        games
+    }
+
+    /// Build the view of a game, including clock- and registry-dependent fields
+    async fn view(&self, id: u64, game: &state::Game) -> GameView {
+        let mut view = GameView::from((id, game));
+        view.move_elapsed_secs = Some(game.move_elapsed(self.now).as_micros() / 1_000_000);
+        view.next_draw_offer_at = game.next_draw_offer_at(self.now).map(|at| at.micros());
+        view.player_x_name = self.display_name(&game.player_x).await;
+        if let Some(player_o) = &game.player_o {
+            view.player_o_name = self.display_name(player_o).await;
+        }
+        view
+    }
+
+    /// Load a game and build its view
+    async fn game_view(&self, id: u64) -> Option<GameView> {
+        let game = match self.state.games.get(&id).await.expect("Failed to load game") {
+            Some(game) => game,
+            None => self
+                .state
+                .archived_games
+                .get(&id)
+                .await
+                .expect("Failed to load archived game")?,
+        };
+        Some(self.view(id, &game).await)
+    }
+
+    /// Copy a game with every mark, seat and seat-based field given to the other side
+    fn swap_colors(game: &state::Game) -> state::Game {
+        let mut swapped = game.clone();
+        for cell in swapped.board.iter_mut().flatten() {
+            *cell = cell.map(state::Player::opponent);
+        }
+        for (player, _, _) in &mut swapped.moves {
+            *player = player.opponent();
+        }
+        if let Some(player_o) = game.player_o {
+            swapped.player_x = player_o;
+            swapped.player_o = Some(game.player_x);
+        }
+        swapped.current_player = game.current_player.opponent();
+        swapped.first_player = game.first_player.opponent();
+        if let state::GameStatus::Won(winner) = game.status {
+            swapped.status = state::GameStatus::Won(winner.opponent());
+        }
+        swapped.draw_offer = game.draw_offer.map(state::Player::opponent);
+        swapped.undo_request = game.undo_request.map(state::Player::opponent);
+        swapped.pending_resign = game.pending_resign.map(|(player, at)| (player.opponent(), at));
+        swapped.x_time_remaining = game.o_time_remaining;
+        swapped.o_time_remaining = game.x_time_remaining;
+        swapped
+    }
+
+    /// Build the views of several games, preserving their order
+    async fn views(&self, games: impl IntoIterator<Item = (u64, state::Game)>) -> Vec<GameView> {
+        let mut views = Vec::new();
+        for (id, game) in games {
+            views.push(self.view(id, &game).await);
+        }
+        views
+    }
+
+    /// Look up the display name registered by an account
+    async fn display_name(&self, owner: &AccountOwner) -> Option<String> {
+        self.state
+            .display_names
+            .get(owner)
+            .await
+            .expect("Failed to load display name")
+    }
 }
 
 #[Object]
 impl QueryRoot {
     /// Get all games
     async fn games(&self) -> Vec<GameView> {
-        self.state
+        self.views(self.load_games().await).await
+    }
+
+    /// Get a page of games in ID order, with at most 100 games per page
+    async fn games_paged(&self, offset: u64, limit: u64) -> GamePage {
+        let mut ids = self
+            .state
             .games
-            .iter()
-            .map(|(id, game)| GameView::from((*id, game)))
+            .indices()
+            .await
+            .expect("Failed to load game IDs");
+        ids.sort_unstable();
+
+        let total = ids.len() as u64;
+        let limit = limit.min(MAX_PAGE_SIZE);
+        let mut items = Vec::new();
+        for id in ids.into_iter().skip(offset as usize).take(limit as usize) {
+            if let Some(game) = self.state.games.get(&id).await.expect("Failed to load game") {
+                items.push(self.view(id, &game).await);
+            }
+        }
+
+        GamePage {
+            has_more: offset.saturating_add(limit) < total,
+            items,
+            total,
+        }
+    }
+
+    /// Get the most recently updated games, newest first, at most 100 at a time
+    async fn recent_games(&self, limit: u64) -> Vec<GameView> {
+        let mut games = self.load_games().await;
+        games.sort_by(|(_, a), (_, b)| b.updated_at.cmp(&a.updated_at));
+        games.truncate(limit.min(MAX_PAGE_SIZE) as usize);
+        self.views(games).await
+    }
+
+    /// Get the latest results recorded by a results-only mirror, newest first
+    async fn recent_results(&self, limit: u64) -> Vec<ResultView> {
+        let mut results = Vec::new();
+        self.state
+            .results
+            .for_each_index_value(|game_id, finished| {
+                results.push((game_id, finished.into_owned()));
+                Ok(())
+            })
+            .await
+            .expect("Failed to load results");
+        results.sort_by(|(a_id, a), (b_id, b)| b.ended_at.cmp(&a.ended_at).then(b_id.cmp(a_id)));
+        results
+            .into_iter()
+            .take(limit.min(MAX_PAGE_SIZE) as usize)
+            .map(|(game_id, finished)| {
+                let (winner, result) = match finished.result {
+                    state::GameResult::WonBy(player) => {
+                        let winner = match player {
+                            state::Player::X => Some(finished.player_x),
+                            state::Player::O => finished.player_o,
+                        };
+                        (winner, format!("Won by {}", player.symbol()))
+                    }
+                    state::GameResult::Draw(reason) => (None, format!("Draw ({})", reason.label())),
+                };
+                ResultView {
+                    game_id,
+                    player_x: finished.player_x.to_string(),
+                    player_o: finished.player_o.map(|player| player.to_string()),
+                    winner: winner.map(|winner| winner.to_string()),
+                    result,
+                    chain_id: finished.chain_id.to_string(),
+                    ended_at: finished.ended_at.micros(),
+                }
+            })
             .collect()
     }
 
+    /// Find games whose title or description contains the query, ignoring case
+    async fn search_games(&self, query: String) -> Vec<GameView> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let games = self
+            .load_games()
+            .await
+            .into_iter()
+            .filter(|(_, game)| game.matches_search(query));
+        self.views(games).await
+    }
+
+    /// Get a game as seen from the other side, with X and O swapped; nothing is stored
+    async fn mirror_perspective(&self, id: u64) -> Option<GameView> {
+        let game = self.state.games.get(&id).await.expect("Failed to load game")?;
+        let mut view = self.view(id, &Self::swap_colors(&game)).await;
+        view.colors_swapped = true;
+        Some(view)
+    }
+
     /// Get a specific game by ID
     async fn game(&self, id: u64) -> Option<GameView> {
+        self.game_view(id).await
+    }
+
+    /// Get several games by ID, in the order requested, with `null` for unknown IDs
+    ///
+    /// Only the first 200 IDs are looked up; the result has one entry per ID looked up.
+    async fn games_by_ids(&self, ids: Vec<u64>) -> Vec<Option<GameView>> {
+        let mut views = Vec::new();
+        for id in ids.into_iter().take(MAX_BATCH_SIZE) {
+            views.push(self.game_view(id).await);
+        }
+        views
+    }
+
+    /// Get the moves played in a game, in order
+    async fn game_moves(&self, id: u64) -> Vec<MoveRecord> {
         self.state
             .games
             .get(&id)
-            .map(|game| GameView::from((id, game)))
+            .await
+            .expect("Failed to load game")
+            .map(|game| {
+                game.moves
+                    .iter()
+                    .enumerate()
+                    .map(|(index, (player, row, col))| MoveRecord {
+                        player: player.symbol().to_string(),
+                        row: *row as u64,
+                        col: *col as u64,
+                        move_number: index as u64 + 1,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 
-    /// Get games where a specific player is participating
-    async fn games_for_player(&self, player: String) -> Vec<GameView> {
+    /// Get the latest moves played across all games, newest first
+    async fn recent_moves(&self, limit: u64) -> Vec<MoveFeedItem> {
+        let limit = limit.min(MAX_PAGE_SIZE) as usize;
+        let mut feed = Vec::new();
+        for (game_id, game) in self.load_games().await {
+            // Each history is in time order, so only its last `limit` moves can make the cut
+            let played = game.moves.iter().zip(&game.move_times).skip(game.handicap_count as usize);
+            feed.extend(played.rev().take(limit).map(|(&(player, row, col), at)| MoveFeedItem {
+                game_id,
+                player: player.symbol().to_string(),
+                row: row as u64,
+                col: col as u64,
+                at: at.micros(),
+            }));
+        }
+        feed.sort_by(|a, b| b.at.cmp(&a.at).then(b.game_id.cmp(&a.game_id)));
+        feed.truncate(limit);
+        feed
+    }
+
+    /// Get the empty cells the current player can play, as [row, col] pairs
+    async fn legal_moves(&self, id: u64) -> Vec<Vec<i32>> {
         self.state
             .games
-            .iter()
-            .filter(|(_, game)| {
-                format!("{:?}", game.player_x) == player
-                    || game.player_o.as_ref().map(|p| format!("{:?}", p)) == Some(player.clone())
+            .get(&id)
+            .await
+            .expect("Failed to load game")
+            .map(|game| {
+                game.legal_moves()
+                    .into_iter()
+                    .map(|(row, col)| vec![row as i32, col as i32])
+                    .collect()
             })
-            .map(|(id, game)| GameView::from((*id, game)))
-            .collect()
+            .unwrap_or_default()
     }
 
-    /// Get games waiting for a second player
-    async fn waiting_games(&self) -> Vec<GameView> {
+    /// Check whether the current player could place a mark at (row, col); false for any invalid input
+    async fn cell_playable(&self, id: u64, row: i32, col: i32) -> bool {
+        let (Ok(row), Ok(col)) = (usize::try_from(row), usize::try_from(col)) else {
+            return false;
+        };
         self.state
             .games
-            .iter()
-            .filter(|(_, game)| matches!(game.status, state::GameStatus::WaitingForPlayer))
-            .map(|(id, game)| GameView::from((*id, game)))
+            .get(&id)
+            .await
+            .expect("Failed to load game")
+            .is_some_and(|game| game.is_legal_move(row, col))
+    }
+
+    /// Show how a game would look after the current player's move at (row, col), without saving it
+    async fn preview_move(&self, id: u64, row: i32, col: i32) -> Option<GameView> {
+        let mut game = self.state.games.get(&id).await.expect("Failed to load game")?;
+        let row = usize::try_from(row).ok()?;
+        let col = usize::try_from(col).ok()?;
+        let player = game.account_for(game.current_player)?;
+        game.make_move(&player, row, col, self.now).ok()?;
+        Some(self.view(id, &game).await)
+    }
+
+    /// Suggest the best move for the current player as [row, col], using the bot's solver
+    async fn hint(&self, id: u64) -> Option<Vec<i32>> {
+        let game = self.state.games.get(&id).await.expect("Failed to load game")?;
+        if game.status != state::GameStatus::InProgress {
+            return None;
+        }
+        state::minimax_move(&game, game.current_player).map(|(row, col)| vec![row as i32, col as i32])
+    }
+
+    /// Check a game's stored state against the rules, returning the violation if any
+    async fn validate_game(&self, id: u64) -> Option<String> {
+        match self.state.games.get(&id).await.expect("Failed to load game") {
+            Some(game) => game.validate().err().map(|e| e.to_string()),
+            None => Some(state::GameError::GameNotFound.to_string()),
+        }
+    }
+
+    /// Check every stored game, hosted or mirrored, against the rules and list those that break them
+    async fn mirror_integrity(&self) -> Vec<IntegrityIssue> {
+        self.load_games()
+            .await
+            .into_iter()
+            .filter_map(|(game_id, game)| {
+                let reason = game.validate().err()?.to_string();
+                Some(IntegrityIssue { game_id, reason })
+            })
             .collect()
     }
 
-    /// Get active games (in progress)
-    async fn active_games(&self) -> Vec<GameView> {
+    /// Export a game as versioned JSON that can be imported again with `ImportGame`
+    async fn export_game(&self, id: u64) -> Option<String> {
+        let game = self.state.games.get(&id).await.expect("Failed to load game")?;
+        let export = state::GameExport::from(&game);
+        Some(serde_json::to_string(&export).expect("Failed to serialize game"))
+    }
+
+    /// Get a game's board and progress without revealing who is playing
+    async fn public_board(&self, id: u64) -> Option<PublicGameView> {
+        let game = self.state.games.get(&id).await.expect("Failed to load game")?;
+        Some(PublicGameView::from(&game))
+    }
+
+    /// Get a compact code for a game's position, usable with `CreateFromPosition`
+    async fn position_code(&self, id: u64) -> Option<String> {
+        let game = self.state.games.get(&id).await.expect("Failed to load game")?;
+        Some(game.position_code())
+    }
+
+    /// Get a text rendering of a game's board
+    async fn board_ascii(&self, id: u64) -> Option<String> {
         self.state
             .games
-            .iter()
-            .filter(|(_, game)| matches!(game.status, state::GameStatus::InProgress))
-            .map(|(id, game)| GameView::from((*id, game)))
-            .collect()
+            .get(&id)
+            .await
+            .expect("Failed to load game")
+            .map(|game| game.render_ascii())
     }
 
-    /// Get completed games
-    async fn completed_games(&self) -> Vec<GameView> {
+    /// Get an SVG image of a game's board
+    async fn board_svg(&self, id: u64) -> Option<String> {
         self.state
             .games
+            .get(&id)
+            .await
+            .expect("Failed to load game")
+            .map(|game| game.render_svg())
+    }
+
+    /// Get the display name registered by an account, given in canonical form
+    async fn resolve_name(&self, owner: String) -> Option<String> {
+        let owner = owner.parse::<AccountOwner>().ok()?;
+        self.display_name(&owner).await
+    }
+
+    /// Get a game's board as a flat list of cells in row-major order
+    ///
+    /// The cell at (row, col) is at index `row * size + col`.
+    async fn board_flat(&self, id: u64) -> Option<Vec<Option<String>>> {
+        let game = self
+            .state
+            .games
+            .get(&id)
+            .await
+            .expect("Failed to load game")?;
+        Some(
+            game.board
+                .iter()
+                .flatten()
+                .map(|cell| cell.map(|p| p.symbol().to_string()))
+                .collect(),
+        )
+    }
+
+    /// Get the role an account plays in a game, given in canonical form
+    async fn my_role(&self, id: u64, me: String) -> PlayerRole {
+        let Some(game) = self.state.games.get(&id).await.expect("Failed to load game") else {
+            return PlayerRole::None;
+        };
+        match game.player_for_str(&me) {
+            Some(state::Player::X) => PlayerRole::PlayerX,
+            Some(state::Player::O) => PlayerRole::PlayerO,
+            None if game.spectators.iter().any(|s| s.to_string() == me) => PlayerRole::Spectator,
+            None => PlayerRole::None,
+        }
+    }
+
+    /// Check whether it is an account's turn in a game in progress, given in canonical form
+    async fn is_my_turn(&self, id: u64, me: String) -> bool {
+        let Some(game) = self.state.games.get(&id).await.expect("Failed to load game") else {
+            return false;
+        };
+        game.status == state::GameStatus::InProgress
+            && game.player_for_str(&me) == Some(game.current_player)
+    }
+
+    /// Explain whether an account, given in canonical form, could move at (row, col) right now
+    async fn explain_move(&self, id: u64, me: String, row: i32, col: i32) -> Option<MoveValidity> {
+        let game = self.state.games.get(&id).await.expect("Failed to load game")?;
+        let validity = if game.status != state::GameStatus::InProgress {
+            MoveValidity::GameNotInProgress
+        } else if let Some(seat) = game.player_for_str(&me) {
+            let in_bounds = |index: i32| usize::try_from(index).is_ok_and(|index| index < game.size);
+            if seat != game.current_player {
+                MoveValidity::NotYourTurn
+            } else if !in_bounds(row) || !in_bounds(col) {
+                MoveValidity::OutOfBounds
+            } else if game.board[row as usize][col as usize].is_some() {
+                MoveValidity::Occupied
+            } else {
+                MoveValidity::Ok
+            }
+        } else {
+            MoveValidity::NotAParticipant
+        };
+        Some(validity)
+    }
+
+    /// Get the games in progress where it is an account's turn, longest waiting first
+    async fn actionable_games(&self, me: String) -> Vec<GameView> {
+        let mut games: Vec<_> = self
+            .load_games()
+            .await
+            .into_iter()
+            .filter(|(_, game)| {
+                game.status == state::GameStatus::InProgress
+                    && game.player_for_str(&me) == Some(game.current_player)
+            })
+            .collect();
+        games.sort_by_key(|(_, game)| game.updated_at);
+        self.views(games).await
+    }
+
+    /// Get challenges waiting for a specific player's answer, oldest first
+    async fn incoming_challenges(&self, me: String) -> Vec<InvitationView> {
+        let mut invitations = Vec::new();
+        self.state
+            .invitations
+            .for_each_index_value(|id, invitation| {
+                if invitation.challengee.to_string() == me {
+                    invitations.push(InvitationView {
+                        id,
+                        challenger: invitation.challenger.to_string(),
+                        challengee: invitation.challengee.to_string(),
+                        created_at: invitation.created_at.micros(),
+                    });
+                }
+                Ok(())
+            })
+            .await
+            .expect("Failed to load invitations");
+        invitations.sort_by_key(|invitation| (invitation.created_at, invitation.id));
+        invitations
+    }
+
+    /// Count the games in progress a specific player is playing, against the most they may play
+    async fn my_active_count(&self, me: String) -> ActiveGameCount {
+        let active = self
+            .load_games()
+            .await
             .iter()
             .filter(|(_, game)| {
-                matches!(
-                    game.status,
-                    state::GameStatus::Won(_) | state::GameStatus::Draw
-                )
+                game.status == state::GameStatus::InProgress && game.player_for_str(&me).is_some()
+            })
+            .count() as u32;
+        ActiveGameCount {
+            active,
+            limit: state::MAX_ACTIVE_GAMES,
+        }
+    }
+
+    /// Get games where a specific player is participating
+    async fn games_for_player(&self, player: String) -> Vec<GameView> {
+        let games = self
+            .load_games()
+            .await
+            .into_iter()
+            .filter(|(_, game)| game.player_for_str(&player).is_some());
+        self.views(games).await
+    }
+
+    /// Get win/loss/draw statistics for a player
+    async fn player_stats(&self, player: String) -> PlayerStats {
+        let mut stats = PlayerStats::default();
+        for (_, game) in self.load_all_games().await {
+            if let Some(seat) = game.player_for_str(&player) {
+                stats.record(&game.status, seat);
+            }
+        }
+        stats.update_win_rate();
+        stats
+    }
+
+    /// Get the results of the games two players have played against each other
+    async fn head_to_head(&self, a: String, b: String) -> H2HRecord {
+        let mut record = H2HRecord::default();
+        for (_, game) in self.load_all_games().await {
+            // Spectators hold no seat, so only games the two played against each other count
+            let (Some(a_seat), Some(b_seat)) = (game.player_for_str(&a), game.player_for_str(&b))
+            else {
+                continue;
+            };
+            if a_seat == b_seat {
+                continue;
+            }
+            match game.status {
+                state::GameStatus::Won(winner) if winner == a_seat => record.a_wins += 1,
+                state::GameStatus::Won(_) => record.b_wins += 1,
+                state::GameStatus::Draw(_) => record.draws += 1,
+                state::GameStatus::WaitingForPlayer | state::GameStatus::InProgress => continue,
+            }
+            record.total += 1;
+        }
+        record
+    }
+
+    /// Get the top players ranked by wins, ties broken by win rate
+    async fn leaderboard(&self, limit: u64) -> Vec<LeaderboardEntry> {
+        let mut players: BTreeMap<String, PlayerStats> = BTreeMap::new();
+        for (_, game) in self.load_all_games().await {
+            players
+                .entry(game.player_x.to_string())
+                .or_default()
+                .record(&game.status, state::Player::X);
+            if let Some(player_o) = &game.player_o {
+                players
+                    .entry(player_o.to_string())
+                    .or_default()
+                    .record(&game.status, state::Player::O);
+            }
+        }
+
+        let mut ranking: Vec<(String, PlayerStats)> = players
+            .into_iter()
+            .map(|(player, mut stats)| {
+                stats.update_win_rate();
+                (player, stats)
+            })
+            .collect();
+        ranking.sort_by(|(_, a), (_, b)| {
+            b.wins
+                .cmp(&a.wins)
+                .then_with(|| b.win_rate.total_cmp(&a.win_rate))
+        });
+
+        ranking
+            .into_iter()
+            .take(limit.min(MAX_PAGE_SIZE) as usize)
+            .map(|(player, stats)| LeaderboardEntry {
+                player,
+                wins: stats.wins,
+                losses: stats.losses,
+                draws: stats.draws,
             })
-            .map(|(id, game)| GameView::from((*id, game)))
             .collect()
     }
 
+    /// Get each player's current run of consecutive wins, longest first
+    async fn win_streaks(&self) -> Vec<StreakEntry> {
+        let mut finished: Vec<_> = self
+            .load_all_games()
+            .await
+            .into_iter()
+            .filter(|(_, game)| game.is_finished())
+            .collect();
+        finished.sort_by_key(|(id, game)| (game.updated_at, *id));
+
+        // A loss or draw ends the streak, so only wins since then count
+        let mut streaks: BTreeMap<String, u64> = BTreeMap::new();
+        for (_, game) in finished {
+            let seats = [(state::Player::X, Some(game.player_x)), (state::Player::O, game.player_o)];
+            for (seat, account) in seats {
+                let Some(account) = account else {
+                    continue;
+                };
+                let streak = streaks.entry(account.to_string()).or_default();
+                if game.status == state::GameStatus::Won(seat) {
+                    *streak += 1;
+                } else {
+                    *streak = 0;
+                }
+            }
+        }
+
+        let mut entries: Vec<_> = streaks
+            .into_iter()
+            .map(|(player, streak)| StreakEntry { player, streak })
+            .collect();
+        entries.sort_by(|a, b| b.streak.cmp(&a.streak).then_with(|| a.player.cmp(&b.player)));
+        entries
+    }
+
+    /// Get games in a status category
+    async fn games_by_status(&self, status: GameStatusFilter) -> Vec<GameView> {
+        let games = self
+            .load_games()
+            .await
+            .into_iter()
+            .filter(|(_, game)| status.matches(&game.status));
+        self.views(games).await
+    }
+
+    /// Get games waiting for a second player, leaving out private ones unless asked
+    async fn waiting_games(&self, #[graphql(default)] include_private: bool) -> Vec<GameView> {
+        let games = self.load_games().await.into_iter().filter(|(_, game)| {
+            GameStatusFilter::Waiting.matches(&game.status) && (include_private || !game.is_private())
+        });
+        self.views(games).await
+    }
+
+    /// Get every known game waiting for an opponent, whichever chain hosts it, oldest first
+    ///
+    /// Mirrored games are included, so a subscriber chain doubles as a lobby for the
+    /// chains it follows.
+    async fn open_tables(&self, #[graphql(default)] include_private: bool) -> Vec<TableListing> {
+        let mut games: Vec<_> = self
+            .load_games()
+            .await
+            .into_iter()
+            .filter(|(_, game)| {
+                game.status == state::GameStatus::WaitingForPlayer
+                    && game.player_o.is_none()
+                    && (include_private || !game.is_private())
+            })
+            .collect();
+        games.sort_by_key(|(id, game)| (game.created_at, *id));
+
+        let mut listings = Vec::new();
+        for (game_id, game) in games {
+            listings.push(TableListing {
+                game_id,
+                chain_id: game.chain_id.to_string(),
+                creator: game.player_x.to_string(),
+                creator_name: self.display_name(&game.player_x).await,
+                size: game.size as u64,
+                win_length: game.win_length as u64,
+                title: game.title,
+                mirrored: game.chain_id != self.chain_id,
+                private: game.is_private(),
+                created_at: game.created_at.micros(),
+            });
+        }
+        listings
+    }
+
+    /// Get the number of open games waiting for a second player
+    async fn queue_depth(&self) -> u64 {
+        self.load_games()
+            .await
+            .iter()
+            .filter(|(_, game)| {
+                matches!(game.status, state::GameStatus::WaitingForPlayer) && game.player_o.is_none()
+            })
+            .count() as u64
+    }
+
+    /// Get a best-of-N series by ID
+    async fn series(&self, id: u64) -> Option<SeriesView> {
+        let series = self
+            .state
+            .series
+            .get(&id)
+            .await
+            .expect("Failed to load series")?;
+        Some(SeriesView {
+            id,
+            player_a: series.player_a.to_string(),
+            player_b: series.player_b.to_string(),
+            best_of: series.best_of,
+            target_wins: series.target_wins,
+            player_a_wins: series.player_a_wins,
+            player_b_wins: series.player_b_wins,
+            game_ids: series.game_ids.clone(),
+            decided: series.is_decided(),
+            winner: series.winner().map(|winner| winner.to_string()),
+            withdrawn_by: series.withdrawn_by.map(|player| player.to_string()),
+        })
+    }
+
+    /// Get a tournament bracket by ID
+    async fn tournament(&self, id: u64) -> Option<TournamentView> {
+        let tournament = self
+            .state
+            .tournaments
+            .get(&id)
+            .await
+            .expect("Failed to load tournament")?;
+        let names = |slots: &Vec<Option<AccountOwner>>| {
+            slots
+                .iter()
+                .map(|slot| slot.map(|player| player.to_string()))
+                .collect::<Vec<_>>()
+        };
+        Some(TournamentView {
+            id,
+            players: tournament.players.iter().map(ToString::to_string).collect(),
+            rounds: tournament.rounds.iter().map(names).collect(),
+            games: tournament.games.clone(),
+            round: tournament.round as u32,
+            finished: tournament.is_finished(),
+            champion: tournament.champion().map(|champion| champion.to_string()),
+        })
+    }
+
+    /// Get active games (in progress)
+    async fn active_games(&self) -> Vec<GameView> {
+        self.games_by_status(GameStatusFilter::InProgress).await
+    }
+
+    /// Get completed games, optionally including archived ones
+    async fn completed_games(&self, #[graphql(default)] include_archived: bool) -> Vec<GameView> {
+        let mut games = self.games_by_status(GameStatusFilter::Finished).await;
+        if include_archived {
+            let archived = self.load_archived_games().await;
+            games.extend(self.views(archived).await);
+            games.sort_by_key(|game| game.id);
+        }
+        games
+    }
+
+    /// Get the games hosted on a chain, leaving out local mirrors unless asked for them
+    async fn games_on_chain(&self, chain_id: String, include_mirrored: bool) -> Vec<GameView> {
+        let games = self.load_games().await.into_iter().filter(|(_, game)| {
+            game.chain_id.to_string() == chain_id && (include_mirrored || game.chain_id == self.chain_id)
+        });
+        self.views(games).await
+    }
+
+    /// Get the number of games hosted on each chain, most active chains first
+    async fn games_by_chain(&self) -> Vec<ChainGameCount> {
+        let mut counts = BTreeMap::new();
+        for (_, game) in self.load_games().await {
+            *counts.entry(game.chain_id.to_string()).or_insert(0u64) += 1;
+        }
+
+        let mut counts: Vec<_> = counts
+            .into_iter()
+            .map(|(chain_id, count)| ChainGameCount { chain_id, count })
+            .collect();
+        counts.sort_by(|a, b| b.count.cmp(&a.count));
+        counts
+    }
+
     /// Get statistics about all games
     async fn stats(&self) -> GameStats {
-        let total_games = self.state.games.len() as u64;
-        let waiting_games = self
-            .state
-            .games
-            .values()
-            .filter(|game| matches!(game.status, state::GameStatus::WaitingForPlayer))
+        let games = self.load_all_games().await;
+        let total_games = games.len() as u64;
+        let waiting_games = games
+            .iter()
+            .filter(|(_, game)| matches!(game.status, state::GameStatus::WaitingForPlayer))
             .count() as u64;
-        let active_games = self
-            .state
-            .games
-            .values()
-            .filter(|game| matches!(game.status, state::GameStatus::InProgress))
+        let active_games = games
+            .iter()
+            .filter(|(_, game)| matches!(game.status, state::GameStatus::InProgress))
             .count() as u64;
-        let completed_games = self
-            .state
-            .games
-            .values()
-            .filter(|game| {
+        let completed_games = games
+            .iter()
+            .filter(|(_, game)| {
                 matches!(
                     game.status,
-                    state::GameStatus::Won(_) | state::GameStatus::Draw
+                    state::GameStatus::Won(_) | state::GameStatus::Draw(_)
                 )
             })
             .count() as u64;
@@ -152,6 +889,54 @@ impl QueryRoot {
             completed_games,
         }
     }
+
+    /// Get a hash of every game's status, board and move count, which changes whenever any game does
+    async fn state_digest(&self) -> String {
+        let games = self.load_games().await;
+        let summary = games
+            .iter()
+            .map(|(id, game)| (id, &game.status, &game.board, game.move_count))
+            .collect::<Vec<_>>();
+        let bytes = bcs::to_bytes(&summary).expect("Failed to serialize games");
+        format!("{:016x}", state::fnv1a_hash(&bytes))
+    }
+
+    /// Count finished games by how they ended
+    async fn outcome_breakdown(&self) -> OutcomeStats {
+        let mut stats = OutcomeStats::default();
+        for (_, game) in self.load_all_games().await {
+            match game.status {
+                state::GameStatus::Won(state::Player::X) => stats.x_wins += 1,
+                state::GameStatus::Won(state::Player::O) => stats.o_wins += 1,
+                state::GameStatus::Draw(_) => stats.draws += 1,
+                state::GameStatus::WaitingForPlayer | state::GameStatus::InProgress => {}
+            }
+        }
+        let decisive = stats.x_wins + stats.o_wins;
+        if decisive > 0 {
+            stats.first_player_advantage = stats.x_wins as f64 / decisive as f64;
+        }
+        stats
+    }
+}
+
+struct SubscriptionRoot {
+    query: QueryRoot,
+}
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// Stream updates of a game
+    ///
+    /// The service only sees the state as of the block it was loaded for, so
+    /// each subscription emits the current state of the game first and then
+    /// ends; clients re-subscribe whenever the node notifies them of a new
+    /// block. Delivery is therefore at-least-once and latest-state: several
+    /// moves may be folded into one update and an unchanged game may be sent
+    /// again. Nothing is emitted for an unknown game.
+    async fn game_updated(&self, id: u64) -> impl Stream<Item = GameView> {
+        stream::iter(self.query.game_view(id).await)
+    }
 }
 
 struct MutationRoot;
@@ -188,3 +973,251 @@ pub struct GameStats {
     pub active_games: u64,
     pub completed_games: u64,
 }
+
+/// Finished games counted by outcome
+#[derive(Default, SimpleObject)]
+pub struct OutcomeStats {
+    pub x_wins: u64,
+    pub o_wins: u64,
+    pub draws: u64,
+    /// Share of decisive games won by X, or zero if none were decisive
+    pub first_player_advantage: f64,
+}
+
+/// Status categories that games can be filtered by
+#[derive(Clone, Copy, Debug, Enum, PartialEq, Eq)]
+pub enum GameStatusFilter {
+    /// Waiting for a second player
+    Waiting,
+    /// Being played
+    InProgress,
+    /// Won by either player
+    Won,
+    /// Ended in a draw
+    Draw,
+    /// Won or drawn
+    Finished,
+}
+
+impl GameStatusFilter {
+    /// Check if a game status falls in this category
+    fn matches(self, status: &state::GameStatus) -> bool {
+        match self {
+            GameStatusFilter::Waiting => matches!(status, state::GameStatus::WaitingForPlayer),
+            GameStatusFilter::InProgress => matches!(status, state::GameStatus::InProgress),
+            GameStatusFilter::Won => matches!(status, state::GameStatus::Won(_)),
+            GameStatusFilter::Draw => matches!(status, state::GameStatus::Draw(_)),
+            GameStatusFilter::Finished => {
+                matches!(status, state::GameStatus::Won(_) | state::GameStatus::Draw(_))
+            }
+        }
+    }
+}
+
+/// Why a move would be accepted or rejected, as checked by `make_move`
+#[derive(Clone, Copy, Debug, Enum, PartialEq, Eq)]
+pub enum MoveValidity {
+    /// The move can be made
+    Ok,
+    /// The other player is to move
+    NotYourTurn,
+    /// The cell already holds a mark
+    Occupied,
+    /// The cell is off the board
+    OutOfBounds,
+    /// The game is waiting for a player or already over
+    GameNotInProgress,
+    /// The account is not playing in the game
+    NotAParticipant,
+}
+
+/// Role of an account in a game
+#[derive(Clone, Copy, Debug, Enum, PartialEq, Eq)]
+pub enum PlayerRole {
+    /// Plays the X seat
+    PlayerX,
+    /// Plays the O seat
+    PlayerO,
+    /// Watches the game
+    Spectator,
+    /// Not involved in the game
+    None,
+}
+
+/// Win/loss/draw record of a single player
+#[derive(Default, SimpleObject)]
+pub struct PlayerStats {
+    pub wins: u64,
+    pub losses: u64,
+    pub draws: u64,
+    pub in_progress: u64,
+    /// Share of finished games won, from 0 to 1
+    pub win_rate: f64,
+}
+
+impl PlayerStats {
+    /// Tally a game in which the player holds the given seat
+    fn record(&mut self, status: &state::GameStatus, seat: state::Player) {
+        match status {
+            state::GameStatus::Won(winner) if *winner == seat => self.wins += 1,
+            state::GameStatus::Won(_) => self.losses += 1,
+            state::GameStatus::Draw(_) => self.draws += 1,
+            state::GameStatus::InProgress => self.in_progress += 1,
+            state::GameStatus::WaitingForPlayer => {}
+        }
+    }
+
+    /// Recompute the win rate from the tallied results
+    fn update_win_rate(&mut self) {
+        let finished = self.wins + self.losses + self.draws;
+        self.win_rate = if finished == 0 {
+            0.0
+        } else {
+            self.wins as f64 / finished as f64
+        };
+    }
+}
+
+/// Results of the finished games between two players
+#[derive(Default, SimpleObject)]
+pub struct H2HRecord {
+    pub a_wins: u64,
+    pub b_wins: u64,
+    pub draws: u64,
+    pub total: u64,
+}
+
+/// A player's row in the leaderboard
+#[derive(SimpleObject)]
+pub struct LeaderboardEntry {
+    pub player: String,
+    pub wins: u64,
+    pub losses: u64,
+    pub draws: u64,
+}
+
+/// Score and games of a best-of-N series
+#[derive(SimpleObject)]
+pub struct SeriesView {
+    pub id: u64,
+    pub player_a: String,
+    pub player_b: String,
+    pub best_of: u32,
+    pub target_wins: u32,
+    pub player_a_wins: u32,
+    pub player_b_wins: u32,
+    pub game_ids: Vec<u64>,
+    pub decided: bool,
+    pub winner: Option<String>,
+    pub withdrawn_by: Option<String>,
+}
+
+/// A tournament bracket, each round listing its entrants in match order
+#[derive(SimpleObject)]
+pub struct TournamentView {
+    pub id: u64,
+    pub players: Vec<String>,
+    pub rounds: Vec<Vec<Option<String>>>,
+    pub games: Vec<Vec<Option<u64>>>,
+    pub round: u32,
+    pub finished: bool,
+    pub champion: Option<String>,
+}
+
+/// A page of games returned by `games_paged`
+#[derive(SimpleObject)]
+pub struct GamePage {
+    pub items: Vec<GameView>,
+    pub total: u64,
+    pub has_more: bool,
+}
+
+/// A single move in a game's history
+#[derive(SimpleObject)]
+pub struct MoveRecord {
+    pub player: String,
+    pub row: u64,
+    pub col: u64,
+    pub move_number: u64,
+}
+
+/// A player's current run of consecutive wins
+#[derive(SimpleObject)]
+pub struct StreakEntry {
+    pub player: String,
+    pub streak: u64,
+}
+
+/// A game waiting for an opponent, as listed in the cross-chain lobby
+#[derive(SimpleObject)]
+pub struct TableListing {
+    pub game_id: u64,
+    /// Chain hosting the game, where `JoinGame` must be executed
+    pub chain_id: String,
+    pub creator: String,
+    pub creator_name: Option<String>,
+    pub size: u64,
+    pub win_length: u64,
+    pub title: Option<String>,
+    /// Whether the game is a mirror of one hosted on another chain
+    pub mirrored: bool,
+    /// Whether joining requires a passcode, with `JoinGamePrivate`
+    pub private: bool,
+    /// Microseconds since the epoch when the game was created
+    pub created_at: u64,
+}
+
+/// Games in progress for one player, and how many they may play at once
+#[derive(SimpleObject)]
+pub struct ActiveGameCount {
+    pub active: u32,
+    pub limit: u32,
+}
+
+/// A challenge waiting for an answer
+#[derive(SimpleObject)]
+pub struct InvitationView {
+    pub id: u64,
+    pub challenger: String,
+    pub challengee: String,
+    /// Microseconds since the epoch when the challenge was issued
+    pub created_at: u64,
+}
+
+/// The result of a game hosted on another chain
+#[derive(SimpleObject)]
+pub struct ResultView {
+    pub game_id: u64,
+    pub player_x: String,
+    pub player_o: Option<String>,
+    pub winner: Option<String>,
+    pub result: String,
+    pub chain_id: String,
+    /// Microseconds since the epoch when the result was received
+    pub ended_at: u64,
+}
+
+/// A move in the feed of recent moves across all games
+#[derive(SimpleObject)]
+pub struct MoveFeedItem {
+    pub game_id: u64,
+    pub player: String,
+    pub row: u64,
+    pub col: u64,
+    /// Microseconds since the epoch when the move was made
+    pub at: u64,
+}
+
+/// Number of games hosted on a chain
+#[derive(SimpleObject)]
+pub struct ChainGameCount {
+    pub chain_id: String,
+    pub count: u64,
+}
+
+/// A stored game whose state breaks an invariant of the rules
+#[derive(SimpleObject)]
+pub struct IntegrityIssue {
+    pub game_id: u64,
+    pub reason: String,
+}
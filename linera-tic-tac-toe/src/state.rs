@@ -1,13 +1,647 @@
-use linera_sdk::base::{AccountOwner, ChainId};
+use std::collections::{HashMap, HashSet};
+
+use linera_sdk::{
+    base::{AccountOwner, ChainId, TimeDelta, Timestamp},
+    views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext},
+};
 use serde::{Deserialize, Serialize};
 
+/// Board size used by the classic game
+pub const DEFAULT_BOARD_SIZE: usize = 3;
+/// Smallest supported board size
+pub const MIN_BOARD_SIZE: usize = 3;
+/// Largest supported board size
+pub const MAX_BOARD_SIZE: usize = 19;
+/// Shortest supported winning run
+pub const MIN_WIN_LENGTH: usize = 3;
+/// Longest display name a player can register, in characters
+pub const MAX_DISPLAY_NAME_LENGTH: usize = 32;
+/// Longest game title, in characters
+pub const MAX_TITLE_LENGTH: usize = 80;
+/// Longest game description, in characters
+pub const MAX_DESCRIPTION_LENGTH: usize = 500;
+/// Time a player has to make each move unless configured otherwise
+pub const DEFAULT_MOVE_DEADLINE_SECS: u64 = 24 * 60 * 60;
+/// Time after creation when a game nobody has joined may be adopted by another player
+pub const ABANDONED_GAME_SECS: u64 = 7 * 24 * 60 * 60;
+/// Most games an account may create within one creation window
+pub const MAX_GAMES_PER_WINDOW: u32 = 20;
+/// Length of the window in which an account's game creations are counted
+pub const CREATION_WINDOW_SECS: u64 = 60;
+/// Most games in progress an account may play at once
+pub const MAX_ACTIVE_GAMES: u32 = 5;
+/// Time after a declined draw offer before another one may be made
+pub const DRAW_OFFER_COOLDOWN_SECS: u64 = 60;
+/// Time a resignation stays pending, during which a move by the resigning player withdraws it
+pub const RESIGN_GRACE_SECS: u64 = 30;
+
+/// Globally unique game identifier, derived from where and by whom a game was created
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GameId(pub u64);
+
+impl GameId {
+    /// Derive the ID of the game created on `chain_id` by `creator` with the given nonce
+    pub fn derive(chain_id: ChainId, creator: AccountOwner, nonce: u64) -> Self {
+        let bytes = bcs::to_bytes(&(chain_id, creator, nonce)).expect("Failed to serialize ID seed");
+        GameId(fnv1a_hash(&bytes))
+    }
+}
+
+impl From<GameId> for u64 {
+    fn from(GameId(id): GameId) -> Self {
+        id
+    }
+}
+
+/// Unused game IDs set aside by `TicTacToeState::reserve_game_ids`, handed out in order
+#[derive(Debug)]
+pub struct GameIdReservation {
+    ids: std::vec::IntoIter<u64>,
+    /// Value of the `next_game_id` nonce once the reserved IDs are used
+    next_nonce: u64,
+}
+
+/// Compute the 64-bit FNV-1a hash of some bytes
+///
+/// Used instead of the standard hasher because its output must never change
+/// between compiler versions.
+pub fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    fnv1a_hash_iter(bytes.iter().copied())
+}
+
+/// Compute the 64-bit FNV-1a hash of bytes produced one at a time
+fn fnv1a_hash_iter(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes.into_iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Hash a passcode into 32 bytes so that only the hash needs to be stored
+///
+/// Four FNV-1a lanes salted with the lane number and the hosting chain keep the
+/// plaintext out of the state without another hashing dependency. This is not a
+/// cryptographic commitment: a short passcode can still be guessed offline.
+pub fn hash_passcode(chain_id: ChainId, passcode: &str) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    for (lane, chunk) in hash.chunks_exact_mut(8).enumerate() {
+        let bytes = bcs::to_bytes(&(lane as u8, chain_id, passcode)).expect("Failed to serialize passcode");
+        chunk.copy_from_slice(&fnv1a_hash(&bytes).to_le_bytes());
+    }
+    hash
+}
+
+/// Errors that can occur when acting on a game
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum GameError {
+    /// The game does not exist
+    GameNotFound,
+    /// It is the other player's turn
+    NotYourTurn,
+    /// The target cell already holds a mark
+    PositionOccupied,
+    /// The cell notation could not be read
+    InvalidCell,
+    /// The target cell is outside the board
+    OutOfBounds,
+    /// The game already has two players
+    GameFull,
+    /// A player tried to play against themselves
+    SelfPlay,
+    /// The game is not in progress
+    NotInProgress,
+    /// A winning run can still be completed by one of the players
+    NotDeadDraw,
+    /// The seat cannot be handed to that account or in this game
+    SeatNotTransferable,
+    /// The game has not finished yet
+    GameNotFinished,
+    /// The caller is not one of the game's players
+    NotAParticipant,
+    /// The caller is not the game's creator
+    NotCreator,
+    /// Only the winner of a decided game may do this
+    NotWinner,
+    /// Player O has not joined yet
+    PlayerNotJoined,
+    /// The board size is outside the supported range
+    InvalidBoardSize,
+    /// The win length is outside the supported range
+    InvalidWinLength,
+    /// The move deadline is not positive
+    InvalidMoveDeadline,
+    /// The total time budget is not positive
+    InvalidTimeBudget,
+    /// The move deadline has not passed yet
+    DeadlineNotPassed,
+    /// The player to move ran out of time, losing the game
+    TimedOut,
+    /// The player attempted an illegal move in a strict game, forfeiting it
+    Forfeited,
+    /// A player tried to claim a timeout on their own turn
+    CannotClaimOwnTurn,
+    /// A draw offer is already pending
+    DrawOfferPending,
+    /// A draw offer was declined too recently to make another
+    DrawOfferTooSoon { retry_in_secs: u64 },
+    /// No draw offer is pending
+    NoDrawOffer,
+    /// A player tried to respond to their own draw offer
+    OwnDrawOffer,
+    /// The player has already requested a rematch
+    RematchAlreadyRequested,
+    /// The player has already asked to delete the game
+    DeleteAlreadyRequested,
+    /// No moves have been made that could be undone
+    NoMovesToUndo,
+    /// Only the player who made the last move may ask to undo it
+    NotLastMover,
+    /// An undo request is already pending
+    UndoPending,
+    /// No undo request is pending
+    NoUndoRequest,
+    /// A player tried to respond to their own undo request
+    OwnUndoRequest,
+    /// A player tried to spectate their own game
+    PlayerCannotSpectate,
+    /// The display name is empty or too long
+    InvalidDisplayName,
+    /// The game title is empty or too long
+    InvalidTitle,
+    /// The game description is too long
+    InvalidDescription,
+    /// No finished game can be evicted to make room for a new one
+    StorageFull,
+    /// A series must have at least one game
+    InvalidSeriesLength,
+    /// The series does not exist
+    SeriesNotFound,
+    /// The series already has a winner
+    SeriesDecided,
+    /// The player has already resigned and the resignation is pending
+    ResignationPending,
+    /// No resignation is pending in the game
+    NoPendingResignation,
+    /// The resignation is still within its grace period
+    ResignationInGrace,
+    /// A tournament needs at least two distinct players
+    InvalidTournament,
+    /// The invitation does not exist or has already been answered
+    InvitationNotFound,
+    /// No more IDs can be generated
+    IdSpaceExhausted,
+    /// Moves have already been made in the game
+    MovesAlreadyMade,
+    /// The game is not played against the bot
+    NotABotGame,
+    /// The opponent plays from the same chain as the creator
+    SameChain,
+    /// The account has created too many games in a short time
+    RateLimited,
+    /// The account is already playing as many games as it may
+    TooManyActiveGames,
+    /// The passcode for a private game is empty
+    InvalidPasscode,
+    /// The game is private and can only be joined with its passcode
+    PasscodeRequired,
+    /// The passcode does not match the private game's
+    WrongPasscode,
+    /// The waiting game has not been abandoned long enough to be adopted
+    GameNotAbandoned,
+    /// Handicap stones overlap, fall off the board or already decide the game
+    InvalidHandicap,
+    /// The stored game breaks an invariant of the rules
+    CorruptGame(String),
+    /// Imported game data could not be read
+    InvalidImport(String),
+    /// The position code could not be read or describes an impossible position
+    InvalidPosition,
+    /// The game is in a state the operation does not apply to
+    InvalidState,
+}
+
+impl std::fmt::Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameError::GameNotFound => write!(f, "Game not found"),
+            GameError::NotYourTurn => write!(f, "Not your turn"),
+            GameError::PositionOccupied => write!(f, "Position already occupied"),
+            GameError::InvalidCell => write!(f, "Cells are written as a column letter and a row number, like a1"),
+            GameError::OutOfBounds => write!(f, "Invalid position"),
+            GameError::GameFull => write!(f, "Game already has two players"),
+            GameError::SelfPlay => write!(f, "Cannot play against yourself"),
+            GameError::NotInProgress => write!(f, "Game is not in progress"),
+            GameError::NotDeadDraw => write!(f, "Game can still be won"),
+            GameError::SeatNotTransferable => write!(f, "Seat cannot be transferred"),
+            GameError::GameNotFinished => write!(f, "Game is not finished"),
+            GameError::NotAParticipant => write!(f, "Only players in this game can do this"),
+            GameError::NotCreator => write!(f, "Only the game creator can do this"),
+            GameError::NotWinner => write!(f, "Only the winner of this game can do this"),
+            GameError::PlayerNotJoined => write!(f, "Player O not joined yet"),
+            GameError::InvalidBoardSize => write!(
+                f,
+                "Board size must be between {} and {}",
+                MIN_BOARD_SIZE, MAX_BOARD_SIZE
+            ),
+            GameError::InvalidWinLength => write!(
+                f,
+                "Win length must be between {} and the board size",
+                MIN_WIN_LENGTH
+            ),
+            GameError::InvalidMoveDeadline => write!(f, "Move deadline must be positive"),
+            GameError::InvalidTimeBudget => write!(f, "Time budget must be positive"),
+            GameError::DeadlineNotPassed => write!(f, "Move deadline has not passed yet"),
+            GameError::TimedOut => write!(f, "Out of time; the game is lost"),
+            GameError::Forfeited => write!(f, "Illegal move in a strict game; the game is forfeited"),
+            GameError::CannotClaimOwnTurn => write!(f, "Cannot claim a timeout on your own turn"),
+            GameError::DrawOfferPending => write!(f, "A draw offer is already pending"),
+            GameError::DrawOfferTooSoon { retry_in_secs } => write!(
+                f,
+                "A draw offer was just declined; try again in {} seconds",
+                retry_in_secs
+            ),
+            GameError::NoDrawOffer => write!(f, "No draw offer is pending"),
+            GameError::OwnDrawOffer => write!(f, "Cannot respond to your own draw offer"),
+            GameError::RematchAlreadyRequested => write!(f, "Rematch already requested"),
+            GameError::DeleteAlreadyRequested => write!(f, "Deletion already requested"),
+            GameError::NoMovesToUndo => write!(f, "No moves to undo"),
+            GameError::NotLastMover => write!(f, "Only the player who made the last move can undo it"),
+            GameError::UndoPending => write!(f, "An undo request is already pending"),
+            GameError::NoUndoRequest => write!(f, "No undo request is pending"),
+            GameError::OwnUndoRequest => write!(f, "Cannot respond to your own undo request"),
+            GameError::PlayerCannotSpectate => write!(f, "Players cannot spectate their own game"),
+            GameError::InvalidDisplayName => write!(
+                f,
+                "Display name must be between 1 and {} characters",
+                MAX_DISPLAY_NAME_LENGTH
+            ),
+            GameError::InvalidTitle => write!(
+                f,
+                "Title must be between 1 and {} characters",
+                MAX_TITLE_LENGTH
+            ),
+            GameError::InvalidDescription => write!(
+                f,
+                "Description must be at most {} characters",
+                MAX_DESCRIPTION_LENGTH
+            ),
+            GameError::StorageFull => write!(f, "Too many games stored; none can be evicted"),
+            GameError::InvalidSeriesLength => write!(f, "A series must have at least one game"),
+            GameError::SeriesNotFound => write!(f, "Series not found"),
+            GameError::SeriesDecided => write!(f, "Series has already been decided"),
+            GameError::ResignationPending => write!(f, "A resignation is already pending"),
+            GameError::NoPendingResignation => write!(f, "No resignation is pending"),
+            GameError::ResignationInGrace => write!(
+                f,
+                "Resignation can still be withdrawn for up to {} seconds",
+                RESIGN_GRACE_SECS
+            ),
+            GameError::InvalidTournament => write!(f, "A tournament needs at least two distinct players"),
+            GameError::InvitationNotFound => write!(f, "Invitation not found"),
+            GameError::IdSpaceExhausted => write!(f, "No more IDs are available"),
+            GameError::MovesAlreadyMade => write!(f, "Moves have already been made"),
+            GameError::NotABotGame => write!(f, "Game is not played against the bot"),
+            GameError::SameChain => write!(f, "Cannot join a game from the chain it was created on"),
+            GameError::RateLimited => write!(f, "Too many games created; try again later"),
+            GameError::TooManyActiveGames => write!(f, "Too many games in progress; finish one first"),
+            GameError::InvalidPasscode => write!(f, "Passcode must not be empty"),
+            GameError::PasscodeRequired => write!(f, "Game is private; a passcode is required"),
+            GameError::WrongPasscode => write!(f, "Wrong passcode"),
+            GameError::GameNotAbandoned => write!(f, "Game has not been abandoned by its creator"),
+            GameError::InvalidHandicap => write!(f, "Handicap stones must be on distinct empty cells and not win"),
+            GameError::CorruptGame(reason) => write!(f, "Game state is corrupt: {}", reason),
+            GameError::InvalidImport(reason) => write!(f, "Invalid game data: {}", reason),
+            GameError::InvalidPosition => write!(f, "Invalid position code"),
+            GameError::InvalidState => write!(f, "Game cannot be changed this way in its current state"),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+/// Check that a display name is between 1 and 32 characters long
+pub fn validate_display_name(name: &str) -> Result<(), GameError> {
+    let length = name.chars().count();
+    if length == 0 || length > MAX_DISPLAY_NAME_LENGTH {
+        return Err(GameError::InvalidDisplayName);
+    }
+    Ok(())
+}
+
+/// Parse a cell written as column letters and a row number, like "a1" or "c3"
+///
+/// Columns run a..z, then aa, ab and so on; rows are numbered from 1 at the top.
+pub fn parse_cell(cell: &str, size: usize) -> Result<(usize, usize), GameError> {
+    let split = cell
+        .find(|c: char| c.is_ascii_digit())
+        .ok_or(GameError::InvalidCell)?;
+    let (letters, digits) = cell.split_at(split);
+    if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(GameError::InvalidCell);
+    }
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(GameError::InvalidCell);
+    }
+
+    let mut column = 0usize;
+    for letter in letters.bytes() {
+        let value = usize::from(letter.to_ascii_lowercase() - b'a') + 1;
+        column = column
+            .checked_mul(26)
+            .and_then(|column| column.checked_add(value))
+            .ok_or(GameError::OutOfBounds)?;
+    }
+    let row = digits.parse::<usize>().map_err(|_| GameError::OutOfBounds)?;
+
+    if row == 0 || row > size || column > size {
+        return Err(GameError::OutOfBounds);
+    }
+    Ok((row - 1, column - 1))
+}
+
+/// Directions scanned for winning runs: horizontal, vertical and both diagonals
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
 /// The application state for the tic-tac-toe game
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(RootView)]
+#[view(context = "ViewStorageContext")]
 pub struct TicTacToeState {
     /// All active games indexed by game ID
-    pub games: std::collections::BTreeMap<u64, Game>,
-    /// Counter for generating unique game IDs
-    pub next_game_id: u64,
+    pub games: MapView<u64, Game>,
+    /// Finished games moved out of `games` to keep scans of active games cheap
+    pub archived_games: MapView<u64, Game>,
+    /// Nonce mixed into the hash that generates unique game IDs
+    pub next_game_id: RegisterView<u64>,
+    /// Display names chosen by players
+    pub display_names: MapView<AccountOwner, String>,
+    /// Best-of-N series indexed by series ID
+    pub series: MapView<u64, Series>,
+    /// Counter for generating unique series IDs
+    pub next_series_id: RegisterView<u64>,
+    /// Single-elimination tournaments indexed by tournament ID
+    pub tournaments: MapView<u64, Tournament>,
+    /// Counter for generating unique tournament IDs
+    pub next_tournament_id: RegisterView<u64>,
+    /// Challenges waiting for an answer, indexed by invitation ID
+    pub invitations: MapView<u64, Invitation>,
+    /// Counter for generating unique invitation IDs
+    pub next_invitation_id: RegisterView<u64>,
+    /// Recent game creations by each account, used for rate limiting
+    pub creations: MapView<AccountOwner, CreationRecord>,
+    /// Results of games hosted on other chains, kept by results-only mirrors
+    pub results: MapView<u64, FinishedGame>,
+}
+
+/// The outcome of a game hosted on another chain
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FinishedGame {
+    /// Player X's account
+    pub player_x: AccountOwner,
+    /// Player O's account, if anyone joined
+    pub player_o: Option<AccountOwner>,
+    /// How the game ended
+    pub result: GameResult,
+    /// Chain hosting the game
+    pub chain_id: ChainId,
+    /// When the result was received
+    pub ended_at: Timestamp,
+}
+
+/// How a game was set up before its first move, enough for another chain to rebuild it
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GameSetup {
+    pub size: usize,
+    pub win_length: usize,
+    /// Seat that makes the first move after the opening marks
+    pub first_player: Player,
+    /// Marks on the board before the first move, from a handicap or a starting position
+    pub opening: Vec<(Player, usize, usize)>,
+    /// Number of opening marks that are handicap stones
+    pub handicap_count: u32,
+}
+
+/// Recent game creations by one account
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct CreationRecord {
+    /// Games created since `window_start`
+    pub created_count: u32,
+    /// Time of the first creation in the current window
+    pub window_start: Timestamp,
+}
+
+impl CreationRecord {
+    /// Count a creation at `now`, rejecting it once the current window is used up
+    ///
+    /// Windows are fixed from their first creation, so creating games steadily
+    /// cannot keep a window open forever.
+    pub fn record(&mut self, now: Timestamp) -> Result<(), GameError> {
+        if now.delta_since(self.window_start) >= TimeDelta::from_secs(CREATION_WINDOW_SECS) {
+            self.created_count = 0;
+            self.window_start = now;
+        }
+        if self.created_count >= MAX_GAMES_PER_WINDOW {
+            return Err(GameError::RateLimited);
+        }
+        self.created_count += 1;
+        Ok(())
+    }
+}
+
+impl TicTacToeState {
+    /// Set aside a fresh globally unique ID for each new game created on `chain_id` by `creators`
+    ///
+    /// Each ID is derived from the chain, the game's creator and the `next_game_id`
+    /// nonce, which is bumped until an unused ID is found. Nothing is written, so
+    /// operations reserve their IDs before any other change and running out of them
+    /// fails cleanly.
+    pub async fn reserve_game_ids(
+        &self,
+        chain_id: ChainId,
+        creators: &[AccountOwner],
+    ) -> Result<GameIdReservation, GameError> {
+        let mut nonce = *self.next_game_id.get();
+        let mut ids = Vec::with_capacity(creators.len());
+        for &creator in creators {
+            let game_id = loop {
+                let GameId(game_id) = GameId::derive(chain_id, creator, nonce);
+                nonce = nonce.checked_add(1).ok_or(GameError::IdSpaceExhausted)?;
+                let taken = ids.contains(&game_id)
+                    || self
+                        .games
+                        .contains_key(&game_id)
+                        .await
+                        .expect("Failed to load game")
+                    || self
+                        .archived_games
+                        .contains_key(&game_id)
+                        .await
+                        .expect("Failed to load archived game");
+                if !taken {
+                    break game_id;
+                }
+            };
+            ids.push(game_id);
+        }
+        Ok(GameIdReservation {
+            ids: ids.into_iter(),
+            next_nonce: nonce,
+        })
+    }
+
+    /// Store a new game under the next ID of a reservation and return that ID
+    pub fn insert_reserved_game(&mut self, reservation: &mut GameIdReservation, game: Game) -> u64 {
+        let game_id = reservation
+            .ids
+            .next()
+            .expect("An ID must be reserved for every new game");
+        self.games
+            .insert(&game_id, game)
+            .expect("Failed to store game");
+        self.next_game_id.set(reservation.next_nonce);
+        game_id
+    }
+
+    /// Find the game to evict before storing another, if storing it would exceed `max_games`
+    pub async fn game_to_evict(&self, max_games: usize) -> Result<Option<u64>, GameError> {
+        Ok(self.games_to_evict(max_games, 1).await?.pop())
+    }
+
+    /// Find the games to evict before storing `new_games` more, one for each new game
+    /// that would take the count over `max_games`
+    ///
+    /// Only finished games are candidates, the least recently updated first; games still
+    /// waiting or in progress are never evicted. A limit of zero means unlimited. Nothing
+    /// is removed here, so callers can finish their other checks before evicting.
+    pub async fn games_to_evict(
+        &self,
+        max_games: usize,
+        new_games: usize,
+    ) -> Result<Vec<u64>, GameError> {
+        if max_games == 0 {
+            return Ok(Vec::new());
+        }
+        let stored = self.games.count().await.expect("Failed to count games");
+        let excess = (stored + new_games)
+            .saturating_sub(max_games)
+            .min(new_games);
+        if excess == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut finished = Vec::new();
+        self.games
+            .for_each_index_value(|id, game| {
+                if game.is_finished() {
+                    finished.push((game.updated_at, id));
+                }
+                Ok(())
+            })
+            .await
+            .expect("Failed to load games");
+        if finished.len() < excess {
+            return Err(GameError::StorageFull);
+        }
+
+        finished.sort_unstable();
+        Ok(finished
+            .into_iter()
+            .take(excess)
+            .map(|(_, id)| id)
+            .collect())
+    }
+
+    /// Count a game creation by `owner`, rejecting it if the account is creating games too quickly
+    pub async fn record_creation(&mut self, owner: &AccountOwner, now: Timestamp) -> Result<(), GameError> {
+        let mut record = self
+            .creations
+            .get(owner)
+            .await
+            .expect("Failed to load creation record")
+            .unwrap_or_default();
+        record.record(now)?;
+        self.creations
+            .insert(owner, record)
+            .expect("Failed to store creation record");
+        Ok(())
+    }
+
+    /// Count the games in progress where `player` holds either seat
+    pub async fn active_game_count(&self, player: &AccountOwner) -> u32 {
+        let mut count = 0;
+        self.games
+            .for_each_index_value(|_, game| {
+                if game.status == GameStatus::InProgress && game.player_for(player).is_some() {
+                    count += 1;
+                }
+                Ok(())
+            })
+            .await
+            .expect("Failed to load games");
+        count
+    }
+
+    /// Reject starting another game for `player` if they already play `limit` games
+    pub async fn check_active_limit(&self, player: &AccountOwner, limit: u32) -> Result<(), GameError> {
+        if self.active_game_count(player).await >= limit {
+            return Err(GameError::TooManyActiveGames);
+        }
+        Ok(())
+    }
+
+    /// Find the oldest game still waiting for an opponent that `player` on `chain_id`
+    /// did not create and may join
+    pub async fn oldest_open_game(&self, player: &AccountOwner, chain_id: ChainId) -> Option<u64> {
+        let mut oldest = None;
+        self.games
+            .for_each_index_value(|id, game| {
+                if game.status == GameStatus::WaitingForPlayer
+                    && game.player_o.is_none()
+                    && !game.is_private()
+                    && &game.player_x != player
+                    && game.can_join_from(chain_id)
+                    && oldest.map_or(true, |oldest| id < oldest)
+                {
+                    oldest = Some(id);
+                }
+                Ok(())
+            })
+            .await
+            .expect("Failed to load games");
+        oldest
+    }
+
+    /// End every game hosted on `chain_id` that has had no move for longer than
+    /// `older_than_secs`, returning the IDs of the games swept
+    pub async fn sweep_inactive(
+        &mut self,
+        chain_id: ChainId,
+        older_than_secs: u64,
+        now: Timestamp,
+    ) -> Vec<u64> {
+        let threshold = TimeDelta::from_secs(older_than_secs);
+        let mut inactive = Vec::new();
+        self.games
+            .for_each_index_value(|id, game| {
+                if game.chain_id == chain_id
+                    && game.status == GameStatus::InProgress
+                    && game.move_elapsed(now) > threshold
+                {
+                    inactive.push(id);
+                }
+                Ok(())
+            })
+            .await
+            .expect("Failed to load games");
+
+        for id in &inactive {
+            if let Some(game) = self.games.get_mut(id).await.expect("Failed to load game") {
+                game.concede_inactive(now);
+            }
+        }
+        inactive
+    }
 }
 
 /// Represents a single tic-tac-toe game
@@ -17,148 +651,2953 @@ pub struct Game {
     pub player_x: AccountOwner,
     /// Player O (second player), None if waiting for opponent
     pub player_o: Option<AccountOwner>,
-    /// Current game board (3x3 grid)
-    pub board: [[Option<Player>; 3]; 3],
+    /// Current game board (size x size grid)
+    pub board: Vec<Vec<Option<Player>>>,
+    /// Number of rows and columns on the board
+    pub size: usize,
+    /// Number of identical marks in a row needed to win
+    pub win_length: usize,
     /// Current player's turn
     pub current_player: Player,
     /// Game status
     pub status: GameStatus,
     /// Chain ID where the game was created
     pub chain_id: ChainId,
+    /// Player with a pending draw offer, if any
+    pub draw_offer: Option<Player>,
+    /// When the last draw offer was declined, until a move is made
+    pub last_draw_offer_at: Option<Timestamp>,
+    /// Player with a pending request to undo their last move, if any
+    pub undo_request: Option<Player>,
+    /// Player who resigned and when, until the grace period ends or they move again
+    pub pending_resign: Option<(Player, Timestamp)>,
+    /// Players who have requested a rematch after the game ended
+    pub rematch_requests: Vec<AccountOwner>,
+    /// Players who have agreed to delete the finished game
+    pub delete_acks: Vec<AccountOwner>,
+    /// Moves played so far, in order, as (player, row, col)
+    pub moves: Vec<(Player, usize, usize)>,
+    /// When each entry of `moves` was made
+    pub move_times: Vec<Timestamp>,
+    /// Cells forming the winning run, set once the game is won on the board
+    pub winning_line: Option<Vec<(usize, usize)>>,
+    /// Time the game started or the last move was made
+    pub last_move_time: Timestamp,
+    /// Seconds the current player has to make a move
+    pub move_deadline_secs: u64,
+    /// Seat that makes the opening move
+    pub first_player: Player,
+    /// Number of moves at the start of the history that are pre-placed handicap stones
+    pub handicap_count: u32,
+    /// Title given by the creator
+    pub title: Option<String>,
+    /// Longer description given by the creator
+    pub description: Option<String>,
+    /// Total seconds each player may spend on their moves, if the game is played on a clock
+    pub time_budget_secs: Option<u64>,
+    /// Time left on player X's clock
+    pub x_time_remaining: Option<TimeDelta>,
+    /// Time left on player O's clock
+    pub o_time_remaining: Option<TimeDelta>,
+    /// Accounts watching the game
+    pub spectators: Vec<AccountOwner>,
+    /// Series this game belongs to, if any
+    pub series_id: Option<u64>,
+    /// Tournament this game belongs to, if any
+    pub tournament_id: Option<u64>,
+    /// Number of moves made so far
+    pub move_count: u32,
+    /// Whether the O seat is played by the built-in bot
+    pub bot: bool,
+    /// How strongly the bot plays
+    pub difficulty: BotDifficulty,
+    /// Seed of the pseudo-random choices made by the bot
+    pub rng_seed: u64,
+    /// Whether an opponent playing from this game's chain may join
+    pub allow_same_chain: bool,
+    /// Hash of the passcode needed to join, if the game is private
+    pub passcode_hash: Option<[u8; 32]>,
+    /// Whether attempting an illegal move forfeits the game
+    pub strict: bool,
+    /// Whether resigning always awards the win, even before any move was made
+    pub competitive: bool,
+    /// Time the game was created
+    pub created_at: Timestamp,
+    /// Time of the last change to the game
+    pub updated_at: Timestamp,
+}
+
+/// Strength of the built-in bot
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum BotDifficulty {
+    /// Plays a random legal move
+    Easy,
+    /// Takes immediate wins and blocks immediate losses, otherwise plays randomly
+    Medium,
+    /// Plays the minimax move
+    #[default]
+    Hard,
+}
+
+/// A best-of-N match between two players
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Series {
+    /// Player who moves first in the opening game
+    pub player_a: AccountOwner,
+    /// Player who moves second in the opening game
+    pub player_b: AccountOwner,
+    /// Maximum number of decisive games in the series
+    pub best_of: u32,
+    /// Wins needed to take the series
+    pub target_wins: u32,
+    /// Games won by player A
+    pub player_a_wins: u32,
+    /// Games won by player B
+    pub player_b_wins: u32,
+    /// Games played in the series, in order
+    pub game_ids: Vec<u64>,
+    /// Player who withdrew, conceding the series to their opponent
+    pub withdrawn_by: Option<AccountOwner>,
+}
+
+impl Series {
+    /// Create a series where the first to win more than half of `best_of` games wins
+    pub fn new(player_a: AccountOwner, player_b: AccountOwner, best_of: u32) -> Result<Self, GameError> {
+        if player_a == player_b {
+            return Err(GameError::SelfPlay);
+        }
+        if best_of == 0 {
+            return Err(GameError::InvalidSeriesLength);
+        }
+        Ok(Self {
+            player_a,
+            player_b,
+            best_of,
+            target_wins: best_of / 2 + 1,
+            player_a_wins: 0,
+            player_b_wins: 0,
+            game_ids: Vec::new(),
+            withdrawn_by: None,
+        })
+    }
+
+    /// Record the result of a game, `None` meaning a draw
+    pub fn record_result(&mut self, winner: Option<AccountOwner>) {
+        if winner == Some(self.player_a) {
+            self.player_a_wins += 1;
+        } else if winner == Some(self.player_b) {
+            self.player_b_wins += 1;
+        }
+    }
+
+    /// Get the series winner, if either player has reached the target
+    pub fn winner(&self) -> Option<AccountOwner> {
+        if self.player_a_wins >= self.target_wins {
+            Some(self.player_a)
+        } else if self.player_b_wins >= self.target_wins {
+            Some(self.player_b)
+        } else {
+            None
+        }
+    }
+
+    /// Concede the series, awarding the opponent the wins they still need
+    ///
+    /// The game in progress is left alone and can be played to completion, but
+    /// its result no longer counts towards the series.
+    pub fn withdraw(&mut self, player: &AccountOwner) -> Result<AccountOwner, GameError> {
+        if *player != self.player_a && *player != self.player_b {
+            return Err(GameError::NotAParticipant);
+        }
+        if self.is_decided() {
+            return Err(GameError::SeriesDecided);
+        }
+        let opponent = if *player == self.player_a {
+            self.player_b_wins = self.target_wins;
+            self.player_b
+        } else {
+            self.player_a_wins = self.target_wins;
+            self.player_a
+        };
+        self.withdrawn_by = Some(*player);
+        Ok(opponent)
+    }
+
+    /// Check if the series has a winner
+    pub fn is_decided(&self) -> bool {
+        self.winner().is_some()
+    }
+
+    /// Get the (X, O) seats for the next game, alternating who moves first
+    pub fn next_seats(&self) -> (AccountOwner, AccountOwner) {
+        if self.game_ids.len() % 2 == 0 {
+            (self.player_a, self.player_b)
+        } else {
+            (self.player_b, self.player_a)
+        }
+    }
+}
+
+/// A challenge from one player to another, waiting to be accepted or declined
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Invitation {
+    /// Player who issued the challenge and will play X
+    pub challenger: AccountOwner,
+    /// Player being challenged, who will play O
+    pub challengee: AccountOwner,
+    /// When the challenge was issued
+    pub created_at: Timestamp,
+}
+
+impl Invitation {
+    /// Create a challenge to `challengee`
+    pub fn new(challenger: AccountOwner, challengee: AccountOwner, now: Timestamp) -> Result<Self, GameError> {
+        if challenger == challengee {
+            return Err(GameError::SelfPlay);
+        }
+        Ok(Self {
+            challenger,
+            challengee,
+            created_at: now,
+        })
+    }
+}
+
+/// A single-elimination bracket between several players
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Tournament {
+    /// Players in seeding order
+    pub players: Vec<AccountOwner>,
+    /// Entrants of each round in bracket order, the last round holding the champion.
+    /// `None` marks a bye in the first round and an undecided match in later ones
+    pub rounds: Vec<Vec<Option<AccountOwner>>>,
+    /// Game deciding each match of each round, once started
+    pub games: Vec<Vec<Option<u64>>>,
+    /// Index of the round being played
+    pub round: usize,
+}
+
+impl Tournament {
+    /// Create a bracket for the players, giving byes to the top seeds
+    /// when the number of players is not a power of two
+    pub fn new(players: Vec<AccountOwner>) -> Result<Self, GameError> {
+        let distinct = players
+            .iter()
+            .enumerate()
+            .all(|(i, player)| !players[..i].contains(player));
+        if players.len() < 2 || !distinct {
+            return Err(GameError::InvalidTournament);
+        }
+
+        let size = players.len().next_power_of_two();
+        let byes = size - players.len();
+        let mut seeds = players.iter().copied();
+        let mut entrants = Vec::with_capacity(size);
+        for slot in 0..size / 2 {
+            entrants.push(seeds.next());
+            entrants.push(if slot < byes { None } else { seeds.next() });
+        }
+
+        let mut rounds = vec![entrants];
+        let mut games = Vec::new();
+        let mut width = size;
+        while width > 1 {
+            games.push(vec![None; width / 2]);
+            width /= 2;
+            rounds.push(vec![None; width]);
+        }
+
+        // Players with a bye go straight through to the second round
+        for slot in 0..byes {
+            rounds[1][slot] = rounds[0][2 * slot];
+        }
+
+        Ok(Self {
+            players,
+            rounds,
+            games,
+            round: 0,
+        })
+    }
+
+    /// Get the matches of the current round that need a game, as (match, X, O)
+    pub fn pending_matches(&self) -> Vec<(usize, AccountOwner, AccountOwner)> {
+        if self.is_finished() {
+            return Vec::new();
+        }
+        let entrants = &self.rounds[self.round];
+        let winners = &self.rounds[self.round + 1];
+        let games = &self.games[self.round];
+        (0..games.len())
+            .filter(|&slot| winners[slot].is_none() && games[slot].is_none())
+            .filter_map(|slot| Some((slot, entrants[2 * slot]?, entrants[2 * slot + 1]?)))
+            .collect()
+    }
+
+    /// Get the match of the current round decided by the game
+    pub fn match_of(&self, game_id: u64) -> Option<usize> {
+        if self.is_finished() {
+            return None;
+        }
+        self.games[self.round]
+            .iter()
+            .position(|game| *game == Some(game_id))
+            .filter(|&slot| self.rounds[self.round + 1][slot].is_none())
+    }
+
+    /// Set the game deciding a match of the current round
+    pub fn assign_game(&mut self, slot: usize, game_id: u64) {
+        self.games[self.round][slot] = Some(game_id);
+    }
+
+    /// Advance the winner of a match, moving on to the next round once every match is decided
+    pub fn record_winner(&mut self, slot: usize, winner: AccountOwner) {
+        let winners = &mut self.rounds[self.round + 1];
+        winners[slot] = Some(winner);
+        if winners.iter().all(Option::is_some) {
+            self.round += 1;
+        }
+    }
+
+    /// Get the tournament winner, once the final is decided
+    pub fn champion(&self) -> Option<AccountOwner> {
+        self.rounds.last().and_then(|last| last[0])
+    }
+
+    /// Check if the final has been decided
+    pub fn is_finished(&self) -> bool {
+        self.champion().is_some()
+    }
 }
 
 /// Represents a player in the game
-#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub enum Player {
+    #[default]
     X,
     O,
 }
 
+impl Player {
+    /// Get the other player
+    pub fn opponent(self) -> Self {
+        match self {
+            Player::X => Player::O,
+            Player::O => Player::X,
+        }
+    }
+
+    /// Get the symbol used to display this player
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Player::X => "X",
+            Player::O => "O",
+        }
+    }
+}
+
 /// Game status
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub enum GameStatus {
     WaitingForPlayer,
     InProgress,
     Won(Player),
-    Draw,
+    Draw(DrawReason),
+}
+
+/// Why a game ended in a draw
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum DrawReason {
+    /// Every cell was filled without a winner
+    BoardFull,
+    /// Both players agreed to a draw
+    Agreement,
+    /// The game was abandoned before anyone moved
+    Timeout,
+    /// Neither player could complete a winning run any more
+    DeadPosition,
+}
+
+impl DrawReason {
+    /// Get the label used to display this reason
+    pub fn label(self) -> &'static str {
+        match self {
+            DrawReason::BoardFull => "board full",
+            DrawReason::Agreement => "agreement",
+            DrawReason::Timeout => "timeout",
+            DrawReason::DeadPosition => "dead position",
+        }
+    }
+}
+
+/// Final result of a game that has ended
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum GameResult {
+    WonBy(Player),
+    Draw(DrawReason),
 }
 
 impl Game {
-    /// Create a new game with player X
-    pub fn new(player_x: AccountOwner, chain_id: ChainId) -> Self {
-        Self {
-            player_x,
-            player_o: None,
-            board: [[None; 3]; 3],
-            current_player: Player::X,
-            status: GameStatus::WaitingForPlayer,
-            chain_id,
+    /// Create a new 3x3 game with player X
+    pub fn new(player_x: AccountOwner, chain_id: ChainId, now: Timestamp) -> Self {
+        Self::with_size(player_x, chain_id, DEFAULT_BOARD_SIZE, DEFAULT_BOARD_SIZE, now)
+    }
+
+    /// Create a new game with player X on a size x size board
+    pub fn new_sized(
+        player_x: AccountOwner,
+        chain_id: ChainId,
+        size: usize,
+        now: Timestamp,
+    ) -> Result<Self, GameError> {
+        Self::new_custom(player_x, chain_id, size, size, now)
+    }
+
+    /// Create a new game with player X where `win_length` in a row wins
+    pub fn new_custom(
+        player_x: AccountOwner,
+        chain_id: ChainId,
+        size: usize,
+        win_length: usize,
+        now: Timestamp,
+    ) -> Result<Self, GameError> {
+        if !(MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&size) {
+            return Err(GameError::InvalidBoardSize);
         }
+        if !(MIN_WIN_LENGTH..=size).contains(&win_length) {
+            return Err(GameError::InvalidWinLength);
+        }
+        Ok(Self::with_size(player_x, chain_id, size, win_length, now))
     }
 
-    /// Join the game as player O
-    pub fn join(&mut self, player_o: AccountOwner) -> Result<(), String> {
-        if self.player_o.is_some() {
-            return Err("Game already has two players".to_string());
+    /// Rebuild a game announced by the chain hosting it, as it was before its first move
+    pub fn from_setup(
+        player_x: AccountOwner,
+        chain_id: ChainId,
+        setup: &GameSetup,
+        now: Timestamp,
+    ) -> Result<Self, GameError> {
+        let mut game = Self::new_custom(player_x, chain_id, setup.size, setup.win_length, now)?
+            .with_first_player(setup.first_player);
+        if setup.handicap_count as usize > setup.opening.len() {
+            return Err(GameError::InvalidPosition);
         }
-        if self.player_x == player_o {
-            return Err("Cannot play against yourself".to_string());
+        for &(player, row, col) in &setup.opening {
+            if row >= game.size || col >= game.size || game.board[row][col].is_some() {
+                return Err(GameError::InvalidPosition);
+            }
+            game.board[row][col] = Some(player);
+            game.moves.push((player, row, col));
+            game.move_times.push(now);
         }
-        self.player_o = Some(player_o);
-        self.status = GameStatus::InProgress;
-        Ok(())
+        game.move_count = game.moves.len() as u32;
+        game.handicap_count = setup.handicap_count;
+        // The players alternate after the handicap stones, starting with the first player
+        if (game.moves.len() - game.handicap_count as usize) % 2 == 1 {
+            game.current_player = setup.first_player.opponent();
+        }
+        game.validate()?;
+        Ok(game)
     }
 
-    /// Make a move on the board
-    pub fn make_move(&mut self, player: &AccountOwner, row: usize, col: usize) -> Result<(), String> {
-        // Validate game state
-        if self.status != GameStatus::InProgress {
-            return Err("Game is not in progress".to_string());
+    /// Describe how the game was set up, for the chains mirroring it
+    ///
+    /// Meant for a game that has just been created, so every move it has is part of its setup.
+    pub fn setup(&self) -> GameSetup {
+        GameSetup {
+            size: self.size,
+            win_length: self.win_length,
+            first_player: self.first_player,
+            opening: self.moves.clone(),
+            handicap_count: self.handicap_count,
         }
+    }
 
-        // Validate player turn
-        let current_player_account = match self.current_player {
-            Player::X => &self.player_x,
-            Player::O => self.player_o.as_ref().ok_or("Player O not joined yet")?,
+    /// Create a game waiting for player O from a position code made by `position_code`
+    ///
+    /// The code only records the board, so the move history is rebuilt by
+    /// alternating the two players' marks in board order.
+    pub fn from_position(
+        player_x: AccountOwner,
+        chain_id: ChainId,
+        code: &str,
+        now: Timestamp,
+    ) -> Result<Self, GameError> {
+        let position = decode_position(code)?;
+        let size = position.board.len();
+        let mut game = Self::new_custom(player_x, chain_id, size, position.win_length, now)?;
+
+        let marks = |player: Player| -> Vec<(usize, usize)> {
+            position
+                .board
+                .iter()
+                .enumerate()
+                .flat_map(|(row, cells)| {
+                    cells
+                        .iter()
+                        .enumerate()
+                        .filter(move |(_, cell)| **cell == Some(player))
+                        .map(move |(col, _)| (row, col))
+                })
+                .collect()
+        };
+        let (x_marks, o_marks) = (marks(Player::X), marks(Player::O));
+        let first_player = match x_marks.len() as isize - o_marks.len() as isize {
+            0 => position.to_move,
+            1 if position.to_move == Player::O => Player::X,
+            -1 if position.to_move == Player::X => Player::O,
+            _ => return Err(GameError::InvalidPosition),
+        };
+        let (first_marks, second_marks) = match first_player {
+            Player::X => (x_marks, o_marks),
+            Player::O => (o_marks, x_marks),
         };
 
-        if player != current_player_account {
-            return Err("Not your turn".to_string());
+        game = game.with_first_player(first_player);
+        for (index, (row, col)) in first_marks.into_iter().enumerate() {
+            game.moves.push((first_player, row, col));
+            if let Some(&(row, col)) = second_marks.get(index) {
+                game.moves.push((first_player.opponent(), row, col));
+            }
         }
+        game.move_count = game.moves.len() as u32;
+        game.move_times = vec![now; game.moves.len()];
+        game.board = position.board;
+        game.current_player = position.to_move;
 
-        // Validate move position
-        if row >= 3 || col >= 3 {
-            return Err("Invalid position".to_string());
+        // Only positions that are still open to play can be set up
+        if game.check_winner().is_some() || game.is_board_full() {
+            return Err(GameError::InvalidPosition);
         }
+        game.validate()?;
+        Ok(game)
+    }
 
-        if self.board[row][col].is_some() {
-            return Err("Position already occupied".to_string());
+    fn with_size(
+        player_x: AccountOwner,
+        chain_id: ChainId,
+        size: usize,
+        win_length: usize,
+        now: Timestamp,
+    ) -> Self {
+        Self {
+            player_x,
+            player_o: None,
+            board: vec![vec![None; size]; size],
+            size,
+            win_length,
+            current_player: Player::X,
+            status: GameStatus::WaitingForPlayer,
+            chain_id,
+            draw_offer: None,
+            last_draw_offer_at: None,
+            undo_request: None,
+            pending_resign: None,
+            rematch_requests: Vec::new(),
+            delete_acks: Vec::new(),
+            moves: Vec::new(),
+            move_times: Vec::new(),
+            winning_line: None,
+            last_move_time: now,
+            move_deadline_secs: DEFAULT_MOVE_DEADLINE_SECS,
+            first_player: Player::X,
+            handicap_count: 0,
+            title: None,
+            description: None,
+            time_budget_secs: None,
+            x_time_remaining: None,
+            o_time_remaining: None,
+            spectators: Vec::new(),
+            series_id: None,
+            tournament_id: None,
+            move_count: 0,
+            bot: false,
+            difficulty: BotDifficulty::default(),
+            rng_seed: fnv1a_hash(
+                &bcs::to_bytes(&(chain_id, player_x, now.micros())).expect("Failed to serialize seed"),
+            ),
+            allow_same_chain: false,
+            passcode_hash: None,
+            strict: false,
+            competitive: false,
+            created_at: now,
+            updated_at: now,
         }
+    }
 
-        // Make the move
-        self.board[row][col] = Some(self.current_player);
+    /// Create a new 3x3 game already joined by player O
+    ///
+    /// Both seats are named up front by a challenge, series, tournament or bot game, all
+    /// set up on the chain hosting the game, so the same-chain check of `join` does not
+    /// apply here. A seat left open later can only be taken through `join`, which checks it.
+    pub fn new_between(
+        player_x: AccountOwner,
+        player_o: AccountOwner,
+        chain_id: ChainId,
+        now: Timestamp,
+    ) -> Result<Self, GameError> {
+        let mut game = Self::new(player_x, chain_id, now);
+        game.seat_player_o(player_o, now)?;
+        Ok(game)
+    }
 
-        // Check for win or draw
-        if let Some(winner) = self.check_winner() {
-            self.status = GameStatus::Won(winner);
-        } else if self.is_board_full() {
-            self.status = GameStatus::Draw;
-        } else {
-            // Switch turns
-            self.current_player = match self.current_player {
-                Player::X => Player::O,
-                Player::O => Player::X,
-            };
-        }
+    /// Create a new 3x3 game where the bot account plays O
+    pub fn new_vs_bot(
+        player_x: AccountOwner,
+        bot: AccountOwner,
+        difficulty: BotDifficulty,
+        chain_id: ChainId,
+        now: Timestamp,
+    ) -> Result<Self, GameError> {
+        let mut game = Self::new_between(player_x, bot, chain_id, now)?;
+        game.bot = true;
+        game.difficulty = difficulty;
+        Ok(game)
+    }
 
-        Ok(())
+    /// Replace the seed of the bot's pseudo-random choices, making them reproducible
+    pub fn with_rng_seed(mut self, rng_seed: u64) -> Self {
+        self.rng_seed = rng_seed;
+        self
     }
 
-    /// Check if there's a winner
-    fn check_winner(&self) -> Option<Player> {
-        // Check rows
-        for row in 0..3 {
-            if let Some(player) = self.board[row][0] {
-                if self.board[row][1] == Some(player) && self.board[row][2] == Some(player) {
-                    return Some(player);
-                }
-            }
+    /// Choose the bot's next move, if `bot` holds the seat to move
+    pub fn bot_move(&self, bot: &AccountOwner) -> Result<(usize, usize), GameError> {
+        if !self.bot {
+            return Err(GameError::NotABotGame);
         }
+        if self.status != GameStatus::InProgress {
+            return Err(GameError::NotInProgress);
+        }
+        if self.account_for(self.current_player).as_ref() != Some(bot) {
+            return Err(GameError::NotYourTurn);
+        }
+        choose_bot_move(self, self.current_player, self.difficulty).ok_or(GameError::NotInProgress)
+    }
 
-        // Check columns
-        for col in 0..3 {
-            if let Some(player) = self.board[0][col] {
-                if self.board[1][col] == Some(player) && self.board[2][col] == Some(player) {
-                    return Some(player);
-                }
-            }
+    /// Set the time each player has to make a move
+    pub fn with_move_deadline(mut self, move_deadline_secs: u64) -> Result<Self, GameError> {
+        if move_deadline_secs == 0 {
+            return Err(GameError::InvalidMoveDeadline);
         }
+        self.move_deadline_secs = move_deadline_secs;
+        Ok(self)
+    }
+
+    /// Choose which seat makes the opening move
+    pub fn with_first_player(mut self, first_player: Player) -> Self {
+        self.first_player = first_player;
+        self.current_player = first_player;
+        self
+    }
 
-        // Check diagonals
-        if let Some(player) = self.board[0][0] {
-            if self.board[1][1] == Some(player) && self.board[2][2] == Some(player) {
-                return Some(player);
+    /// Pre-place handicap stones, recorded at the start of the move history
+    ///
+    /// The first real move goes to the opponent of whoever received the last stone.
+    pub fn with_handicap(mut self, handicap: &[(Player, usize, usize)]) -> Result<Self, GameError> {
+        if !self.moves.is_empty() {
+            return Err(GameError::MovesAlreadyMade);
+        }
+        for &(player, row, col) in handicap {
+            if row >= self.size || col >= self.size || self.board[row][col].is_some() {
+                return Err(GameError::InvalidHandicap);
             }
+            self.board[row][col] = Some(player);
+            self.moves.push((player, row, col));
+            self.move_times.push(self.created_at);
+        }
+        if self.check_winner().is_some() || self.is_board_full() {
+            return Err(GameError::InvalidHandicap);
         }
 
-        if let Some(player) = self.board[0][2] {
-            if self.board[1][1] == Some(player) && self.board[2][0] == Some(player) {
-                return Some(player);
-            }
+        self.move_count = self.moves.len() as u32;
+        self.handicap_count = self.move_count;
+        if let Some(&(last, _, _)) = self.moves.last() {
+            self = self.with_first_player(last.opponent());
         }
+        Ok(self)
+    }
 
-        None
+    /// Give each player a total time budget for all their moves
+    pub fn with_time_budget(mut self, time_budget_secs: u64) -> Result<Self, GameError> {
+        if time_budget_secs == 0 {
+            return Err(GameError::InvalidTimeBudget);
+        }
+        self.time_budget_secs = Some(time_budget_secs);
+        self.x_time_remaining = Some(TimeDelta::from_secs(time_budget_secs));
+        self.o_time_remaining = Some(TimeDelta::from_secs(time_budget_secs));
+        Ok(self)
     }
 
-    /// Check if the board is full
-    fn is_board_full(&self) -> bool {
-        for row in 0..3 {
-            for col in 0..3 {
-                if self.board[row][col].is_none() {
-                    return false;
-                }
+    /// Attach a title and description to help players find the game
+    pub fn with_details(
+        mut self,
+        title: Option<String>,
+        description: Option<String>,
+    ) -> Result<Self, GameError> {
+        if let Some(title) = &title {
+            let length = title.trim().chars().count();
+            if length == 0 || length > MAX_TITLE_LENGTH {
+                return Err(GameError::InvalidTitle);
             }
         }
-        true
+        if let Some(description) = &description {
+            if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+                return Err(GameError::InvalidDescription);
+            }
+        }
+        self.title = title;
+        self.description = description;
+        Ok(self)
+    }
+
+    /// Check if the title or description contains `query`, ignoring case
+    pub fn matches_search(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        [&self.title, &self.description]
+            .into_iter()
+            .flatten()
+            .any(|text| text.to_lowercase().contains(&query))
+    }
+
+    /// Make attempting an illegal move forfeit the game
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Make resigning award the win to the opponent even when no move has been made
+    pub fn with_competitive(mut self, competitive: bool) -> Self {
+        self.competitive = competitive;
+        self
+    }
+
+    /// Make the game private, so that joining requires the passcode
+    pub fn with_passcode(mut self, passcode: &str) -> Result<Self, GameError> {
+        if passcode.is_empty() {
+            return Err(GameError::InvalidPasscode);
+        }
+        self.passcode_hash = Some(hash_passcode(self.chain_id, passcode));
+        Ok(self)
+    }
+
+    /// Check if joining the game requires a passcode
+    pub fn is_private(&self) -> bool {
+        self.passcode_hash.is_some()
+    }
+
+    /// Allow an opponent playing from the game's own chain to join
+    pub fn with_same_chain_allowed(mut self, allow_same_chain: bool) -> Self {
+        self.allow_same_chain = allow_same_chain;
+        self
+    }
+
+    /// Join the game as player O from `joiner_chain`, with the passcode if the game is private
+    pub fn join(
+        &mut self,
+        player_o: AccountOwner,
+        joiner_chain: ChainId,
+        passcode: Option<&str>,
+        now: Timestamp,
+    ) -> Result<(), GameError> {
+        if !self.can_join_from(joiner_chain) {
+            return Err(GameError::SameChain);
+        }
+        if let Some(expected) = self.passcode_hash {
+            let passcode = passcode.ok_or(GameError::PasscodeRequired)?;
+            if hash_passcode(self.chain_id, passcode) != expected {
+                return Err(GameError::WrongPasscode);
+            }
+        }
+        self.seat_player_o(player_o, now)
+    }
+
+    /// Check if a player on `joiner_chain` is allowed to join
+    pub fn can_join_from(&self, joiner_chain: ChainId) -> bool {
+        self.allow_same_chain || joiner_chain != self.chain_id
+    }
+
+    /// Seat player O and start the game, without checking where they play from
+    pub fn seat_player_o(&mut self, player_o: AccountOwner, now: Timestamp) -> Result<(), GameError> {
+        if self.player_o.is_some() {
+            return Err(GameError::GameFull);
+        }
+        if self.player_x == player_o {
+            return Err(GameError::SelfPlay);
+        }
+        self.player_o = Some(player_o);
+        self.status = GameStatus::InProgress;
+        self.last_move_time = now;
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Add a spectator, returning false if they were already watching
+    pub fn spectate(&mut self, spectator: AccountOwner, now: Timestamp) -> Result<bool, GameError> {
+        if self.player_for(&spectator).is_some() {
+            return Err(GameError::PlayerCannotSpectate);
+        }
+        if self.spectators.contains(&spectator) {
+            return Ok(false);
+        }
+        self.spectators.push(spectator);
+        self.updated_at = now;
+        Ok(true)
+    }
+
+    /// Clear the board and reopen the game for a new opponent, as its creator
+    ///
+    /// This is a recovery tool: only a game that has not started yet or that no
+    /// longer passes `validate` can be reset, and never one counting towards a match.
+    pub fn reset(&mut self, caller: &AccountOwner, now: Timestamp) -> Result<(), GameError> {
+        if &self.player_x != caller {
+            return Err(GameError::NotCreator);
+        }
+        let recoverable = self.moves.is_empty() || self.validate().is_err();
+        if self.is_match_game() || !recoverable {
+            return Err(GameError::InvalidState);
+        }
+        self.apply_remote_reset(now);
+        Ok(())
+    }
+
+    /// Clear the board and reopen the game, as reset on the chain hosting it
+    pub fn apply_remote_reset(&mut self, now: Timestamp) {
+        self.board = vec![vec![None; self.size]; self.size];
+        self.moves.clear();
+        self.move_times.clear();
+        self.move_count = 0;
+        self.handicap_count = 0;
+        self.winning_line = None;
+        self.player_o = None;
+        self.status = GameStatus::WaitingForPlayer;
+        self.current_player = self.first_player;
+        self.draw_offer = None;
+        self.undo_request = None;
+        self.pending_resign = None;
+        self.rematch_requests.clear();
+        self.delete_acks.clear();
+        let budget = self.time_budget_secs.map(TimeDelta::from_secs);
+        self.x_time_remaining = budget;
+        self.o_time_remaining = budget;
+        self.last_move_time = now;
+        self.updated_at = now;
+    }
+
+    /// Take over the creator seat of a game nobody joined before it was abandoned,
+    /// returning the replaced creator
+    pub fn adopt(&mut self, adopter: AccountOwner, now: Timestamp) -> Result<AccountOwner, GameError> {
+        if self.status != GameStatus::WaitingForPlayer || self.player_o.is_some() {
+            return Err(GameError::GameFull);
+        }
+        if self.player_x == adopter
+            || now.delta_since(self.created_at) <= TimeDelta::from_secs(ABANDONED_GAME_SECS)
+        {
+            return Err(GameError::GameNotAbandoned);
+        }
+
+        let old = self.player_x;
+        self.player_x = adopter;
+        // The adopted game counts as new, so it cannot be adopted straight away again
+        self.created_at = now;
+        self.updated_at = now;
+        Ok(old)
+    }
+
+    /// Hand the caller's seat to another account, returning the seat
+    ///
+    /// Series and tournament games are excluded because their standings track accounts.
+    pub fn transfer_seat(
+        &mut self,
+        from: &AccountOwner,
+        to: AccountOwner,
+        now: Timestamp,
+    ) -> Result<Player, GameError> {
+        let seat = self.player_for(from).ok_or(GameError::NotAParticipant)?;
+        if self.is_finished() {
+            return Err(GameError::NotInProgress);
+        }
+        if self.player_for(&to).is_some() {
+            return Err(GameError::SelfPlay);
+        }
+        if self.bot || self.is_match_game() {
+            return Err(GameError::SeatNotTransferable);
+        }
+        self.replace_seat(from, to, now);
+        Ok(seat)
+    }
+
+    /// Move whatever seat `from` holds to `to`, returning false if `from` holds none
+    pub fn replace_seat(&mut self, from: &AccountOwner, to: AccountOwner, now: Timestamp) -> bool {
+        match self.player_for(from) {
+            Some(Player::X) => self.player_x = to,
+            Some(Player::O) => self.player_o = Some(to),
+            None => return false,
+        }
+        // Requests are made by accounts, so they follow the seat; draw offers and
+        // the turn belong to the seat and stay as they are
+        for account in self.rematch_requests.iter_mut().chain(&mut self.delete_acks) {
+            if *account == *from {
+                *account = to;
+            }
+        }
+        self.spectators.retain(|spectator| *spectator != to);
+        self.updated_at = now;
+        true
+    }
+
+    /// Check whether a caller may abort this game before anyone joins
+    pub fn can_abort(&self, caller: &AccountOwner) -> Result<(), GameError> {
+        if &self.player_x != caller {
+            return Err(GameError::NotCreator);
+        }
+        if self.player_o.is_some() || self.status != GameStatus::WaitingForPlayer {
+            return Err(GameError::GameFull);
+        }
+        Ok(())
+    }
+
+    /// Check whether a caller may archive this game once it is over
+    ///
+    /// Either player may archive a draw, but only the winner a decided game, so a
+    /// loser cannot tuck their losses away.
+    pub fn can_archive(&self, caller: &AccountOwner) -> Result<(), GameError> {
+        let Some(seat) = self.player_for(caller) else {
+            return Err(GameError::NotAParticipant);
+        };
+        match self.status {
+            GameStatus::WaitingForPlayer | GameStatus::InProgress => Err(GameError::GameNotFinished),
+            GameStatus::Won(winner) if winner != seat => Err(GameError::NotWinner),
+            GameStatus::Won(_) | GameStatus::Draw(_) => Ok(()),
+        }
+    }
+
+    /// Give up the player O seat before any move is made
+    pub fn leave(&mut self, player: &AccountOwner, now: Timestamp) -> Result<(), GameError> {
+        if self.player_o.as_ref() != Some(player) {
+            return Err(GameError::NotAParticipant);
+        }
+        if self.status != GameStatus::InProgress {
+            return Err(GameError::NotInProgress);
+        }
+        if !self.moves.is_empty() {
+            return Err(GameError::MovesAlreadyMade);
+        }
+        self.player_o = None;
+        self.status = GameStatus::WaitingForPlayer;
+        self.draw_offer = None;
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Check if no player can still complete a winning run, so the game can only
+    /// end in a draw
+    ///
+    /// A run is out of reach for a player once it holds an opponent's mark or needs
+    /// more marks than the moves that player has left before the board fills up.
+    pub fn is_dead_draw(&self) -> bool {
+        let reach = self.win_length as isize - 1;
+        let size = self.size as isize;
+        let empty = self.empty_cells().len();
+        let moves_left = |player: Player| {
+            if player == self.current_player {
+                empty.div_ceil(2)
+            } else {
+                empty / 2
+            }
+        };
+        for row in 0..size {
+            for col in 0..size {
+                for (dr, dc) in DIRECTIONS {
+                    let (end_row, end_col) = (row + dr * reach, col + dc * reach);
+                    if end_row < 0 || end_col < 0 || end_row >= size || end_col >= size {
+                        continue;
+                    }
+                    let marks: Vec<Player> = (0..=reach)
+                        .filter_map(|k| self.cell_at(row + dr * k, col + dc * k))
+                        .collect();
+                    let missing = self.win_length as usize - marks.len();
+                    for player in [Player::X, Player::O] {
+                        if !marks.contains(&player.opponent()) && missing <= moves_left(player) {
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// End a game in progress as a draw because neither player can win any more
+    pub fn claim_dead_draw(&mut self, claimant: &AccountOwner, now: Timestamp) -> Result<(), GameError> {
+        if self.player_for(claimant).is_none() {
+            return Err(GameError::NotAParticipant);
+        }
+        if self.status != GameStatus::InProgress {
+            return Err(GameError::NotInProgress);
+        }
+        if !self.is_dead_draw() {
+            return Err(GameError::NotDeadDraw);
+        }
+
+        self.status = GameStatus::Draw(DrawReason::DeadPosition);
+        self.draw_offer = None;
+        self.undo_request = None;
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Claim a win because the opponent let their move deadline pass
+    pub fn claim_timeout_win(&mut self, claimant: &AccountOwner, now: Timestamp) -> Result<(), GameError> {
+        let claimer = self
+            .player_for(claimant)
+            .ok_or(GameError::NotAParticipant)?;
+
+        if self.status != GameStatus::InProgress {
+            return Err(GameError::NotInProgress);
+        }
+
+        if claimer == self.current_player {
+            return Err(GameError::CannotClaimOwnTurn);
+        }
+
+        if !self.is_move_overdue(now) && !self.is_out_of_time(now) {
+            return Err(GameError::DeadlineNotPassed);
+        }
+
+        self.status = GameStatus::Won(claimer);
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// End an abandoned game, awarding it to the player waiting on the opponent,
+    /// or as a draw if no move was ever made
+    pub fn concede_inactive(&mut self, now: Timestamp) {
+        self.status = if self.moves.is_empty() {
+            GameStatus::Draw(DrawReason::Timeout)
+        } else {
+            GameStatus::Won(self.current_player.opponent())
+        };
+        self.draw_offer = None;
+        self.undo_request = None;
+        self.updated_at = now;
+    }
+
+    /// Time elapsed since the game started or the last move was made
+    pub fn move_elapsed(&self, now: Timestamp) -> TimeDelta {
+        now.delta_since(self.last_move_time)
+    }
+
+    /// Check if the current player has exceeded the move deadline
+    pub fn is_move_overdue(&self, now: Timestamp) -> bool {
+        self.move_elapsed(now) > TimeDelta::from_secs(self.move_deadline_secs)
+    }
+
+    /// Time left on a player's clock, if the game is played on a clock
+    pub fn time_remaining(&self, player: Player) -> Option<TimeDelta> {
+        match player {
+            Player::X => self.x_time_remaining,
+            Player::O => self.o_time_remaining,
+        }
+    }
+
+    /// Check if the current player has used up their total time budget
+    pub fn is_out_of_time(&self, now: Timestamp) -> bool {
+        self.time_remaining(self.current_player)
+            .is_some_and(|remaining| self.move_elapsed(now) >= remaining)
+    }
+
+    /// Charge the time spent on the current move to the current player's clock
+    fn charge_clock(&mut self, now: Timestamp) {
+        let elapsed = self.move_elapsed(now);
+        let clock = match self.current_player {
+            Player::X => &mut self.x_time_remaining,
+            Player::O => &mut self.o_time_remaining,
+        };
+        if let Some(remaining) = clock {
+            let left = remaining.as_micros().saturating_sub(elapsed.as_micros());
+            *remaining = TimeDelta::from_micros(left);
+        }
+    }
+
+    /// Resign the game, awarding the win to the opponent once the grace period has passed
+    pub fn resign(&mut self, player: &AccountOwner, now: Timestamp) -> Result<(), GameError> {
+        let resigner = self
+            .player_for(player)
+            .ok_or(GameError::NotAParticipant)?;
+
+        if self.status != GameStatus::InProgress {
+            return Err(GameError::NotInProgress);
+        }
+
+        if self.pending_resign.is_some() {
+            return Err(GameError::ResignationPending);
+        }
+
+        self.pending_resign = Some((resigner, now));
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// End the game with a pending resignation whose grace period has elapsed, returning the resigner
+    ///
+    /// Unless the game is competitive, resigning before any move was made ends it
+    /// in a draw instead of handing the opponent a win they did nothing for.
+    pub fn finalize_resign(&mut self, now: Timestamp) -> Result<Player, GameError> {
+        if self.status != GameStatus::InProgress {
+            return Err(GameError::NotInProgress);
+        }
+        let (resigner, resigned_at) = self.pending_resign.ok_or(GameError::NoPendingResignation)?;
+        if now.delta_since(resigned_at) <= TimeDelta::from_secs(RESIGN_GRACE_SECS) {
+            return Err(GameError::ResignationInGrace);
+        }
+
+        self.status = if self.moves.is_empty() && !self.competitive {
+            GameStatus::Draw(DrawReason::Agreement)
+        } else {
+            GameStatus::Won(resigner.opponent())
+        };
+        self.pending_resign = None;
+        self.draw_offer = None;
+        self.undo_request = None;
+        self.updated_at = now;
+        Ok(resigner)
+    }
+
+    /// Offer a draw to the opponent
+    pub fn offer_draw(&mut self, player: &AccountOwner, now: Timestamp) -> Result<(), GameError> {
+        let offerer = self
+            .player_for(player)
+            .ok_or(GameError::NotAParticipant)?;
+
+        if self.status != GameStatus::InProgress {
+            return Err(GameError::NotInProgress);
+        }
+
+        if self.draw_offer.is_some() {
+            return Err(GameError::DrawOfferPending);
+        }
+
+        if let Some(declined_at) = self.last_draw_offer_at {
+            let cooldown = DRAW_OFFER_COOLDOWN_SECS * 1_000_000;
+            let elapsed = now.delta_since(declined_at).as_micros();
+            if elapsed < cooldown {
+                return Err(GameError::DrawOfferTooSoon {
+                    retry_in_secs: (cooldown - elapsed).div_ceil(1_000_000),
+                });
+            }
+        }
+
+        self.draw_offer = Some(offerer);
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Get when a new draw offer may be made, if a declined offer is still cooling down
+    pub fn next_draw_offer_at(&self, now: Timestamp) -> Option<Timestamp> {
+        let declined_at = self.last_draw_offer_at?;
+        let allowed_at = Timestamp::from(declined_at.micros() + DRAW_OFFER_COOLDOWN_SECS * 1_000_000);
+        (allowed_at > now).then_some(allowed_at)
+    }
+
+    /// Accept or decline a pending draw offer
+    pub fn respond_draw(
+        &mut self,
+        player: &AccountOwner,
+        accept: bool,
+        now: Timestamp,
+    ) -> Result<(), GameError> {
+        let responder = self
+            .player_for(player)
+            .ok_or(GameError::NotAParticipant)?;
+
+        if self.status != GameStatus::InProgress {
+            return Err(GameError::NotInProgress);
+        }
+
+        match self.draw_offer {
+            None => return Err(GameError::NoDrawOffer),
+            Some(offerer) if offerer == responder => {
+                return Err(GameError::OwnDrawOffer);
+            }
+            Some(_) => {}
+        }
+
+        self.draw_offer = None;
+        if accept {
+            self.status = GameStatus::Draw(DrawReason::Agreement);
+        } else {
+            self.last_draw_offer_at = Some(now);
+        }
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Ask the opponent to take back the last move
+    pub fn request_undo(&mut self, player: &AccountOwner, now: Timestamp) -> Result<(), GameError> {
+        let requester = self
+            .player_for(player)
+            .ok_or(GameError::NotAParticipant)?;
+
+        if !self.can_undo() {
+            return Err(GameError::NotInProgress);
+        }
+
+        // Handicap stones are part of the setup and cannot be taken back
+        if self.moves.len() <= self.handicap_count as usize {
+            return Err(GameError::NoMovesToUndo);
+        }
+        let &(last_mover, _, _) = self.moves.last().ok_or(GameError::NoMovesToUndo)?;
+        if last_mover != requester {
+            return Err(GameError::NotLastMover);
+        }
+
+        if self.undo_request.is_some() {
+            return Err(GameError::UndoPending);
+        }
+
+        self.undo_request = Some(requester);
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Accept or decline a pending undo request, taking back the last move if accepted
+    pub fn respond_undo(
+        &mut self,
+        player: &AccountOwner,
+        accept: bool,
+        now: Timestamp,
+    ) -> Result<(), GameError> {
+        let responder = self
+            .player_for(player)
+            .ok_or(GameError::NotAParticipant)?;
+
+        match self.undo_request {
+            None => return Err(GameError::NoUndoRequest),
+            Some(requester) if requester == responder => {
+                return Err(GameError::OwnUndoRequest);
+            }
+            Some(_) => {}
+        }
+
+        if !self.can_undo() {
+            return Err(GameError::NotInProgress);
+        }
+
+        // Taking the move back also clears the request, and changes nothing if it fails
+        if accept {
+            self.take_back_last_move(now)?;
+        } else {
+            self.undo_request = None;
+            self.updated_at = now;
+        }
+        Ok(())
+    }
+
+    /// Mirror a take-back approved on the host chain
+    ///
+    /// The undo is only applied if the cell matches the last move, so a
+    /// replayed message cannot take back an earlier move.
+    pub fn apply_remote_undo(&mut self, row: usize, col: usize, now: Timestamp) -> Result<(), GameError> {
+        match self.moves.last() {
+            Some(&(_, last_row, last_col)) if (last_row, last_col) == (row, col) => {
+                self.take_back_last_move(now)
+            }
+            _ => Err(GameError::NoMovesToUndo),
+        }
+    }
+
+    /// Whether the result of the game counts towards a series or tournament
+    fn is_match_game(&self) -> bool {
+        self.series_id.is_some() || self.tournament_id.is_some()
+    }
+
+    /// Whether the game is in a state where the last move can be taken back
+    fn can_undo(&self) -> bool {
+        match self.status {
+            GameStatus::InProgress => true,
+            // Only a result decided by the last move on the board can be taken back,
+            // and never once it has counted towards a series or tournament
+            GameStatus::Won(_) => self.winning_line.is_some() && !self.is_match_game(),
+            GameStatus::Draw(DrawReason::BoardFull) => !self.is_match_game(),
+            GameStatus::Draw(_) => false,
+            GameStatus::WaitingForPlayer => false,
+        }
+    }
+
+    /// Request a rematch, returning true once both players have requested one
+    pub fn request_rematch(&mut self, player: &AccountOwner, now: Timestamp) -> Result<bool, GameError> {
+        if self.player_for(player).is_none() {
+            return Err(GameError::NotAParticipant);
+        }
+
+        if !matches!(self.status, GameStatus::Won(_) | GameStatus::Draw(_)) {
+            return Err(GameError::GameNotFinished);
+        }
+
+        if self.rematch_requests.contains(player) {
+            return Err(GameError::RematchAlreadyRequested);
+        }
+
+        self.rematch_requests.push(*player);
+        self.updated_at = now;
+        Ok(self.rematch_requests.len() == 2)
+    }
+
+    /// Agree to delete a finished game, returning true once every human player has agreed
+    pub fn request_delete(&mut self, player: &AccountOwner, now: Timestamp) -> Result<bool, GameError> {
+        if self.player_for(player).is_none() {
+            return Err(GameError::NotAParticipant);
+        }
+
+        if !matches!(self.status, GameStatus::Won(_) | GameStatus::Draw(_)) {
+            return Err(GameError::GameNotFinished);
+        }
+
+        if self.delete_acks.contains(player) {
+            return Err(GameError::DeleteAlreadyRequested);
+        }
+
+        self.delete_acks.push(*player);
+        self.updated_at = now;
+        // The bot never asks, so its opponent decides alone
+        let needed = if self.bot { 1 } else { 2 };
+        Ok(self.delete_acks.len() == needed)
+    }
+
+    /// Create a rematch of this game with the players' seats swapped
+    pub fn rematch(&self, now: Timestamp) -> Result<Self, GameError> {
+        let player_o = self.player_o.ok_or(GameError::PlayerNotJoined)?;
+        let mut game = Game::with_size(player_o, self.chain_id, self.size, self.win_length, now);
+        game.player_o = Some(self.player_x);
+        game.status = GameStatus::InProgress;
+        game.move_deadline_secs = self.move_deadline_secs;
+        let game = game
+            .with_first_player(self.first_player)
+            .with_strict(self.strict)
+            .with_competitive(self.competitive);
+        match self.time_budget_secs {
+            Some(secs) => game.with_time_budget(secs),
+            None => Ok(game),
+        }
+    }
+
+    /// Get the final result, if the game has ended
+    pub fn result(&self) -> Option<GameResult> {
+        match self.status {
+            GameStatus::Won(winner) => Some(GameResult::WonBy(winner)),
+            GameStatus::Draw(reason) => Some(GameResult::Draw(reason)),
+            GameStatus::WaitingForPlayer | GameStatus::InProgress => None,
+        }
+    }
+
+    /// Record the result announced by the chain hosting this game on a local mirror
+    pub fn apply_remote_result(&mut self, result: GameResult, now: Timestamp) {
+        self.status = match result {
+            GameResult::WonBy(winner) => GameStatus::Won(winner),
+            GameResult::Draw(reason) => GameStatus::Draw(reason),
+        };
+        if let GameResult::WonBy(winner) = result {
+            self.winning_line = self
+                .check_winner()
+                .filter(|(player, _)| *player == winner)
+                .map(|(_, line)| line);
+        }
+        self.draw_offer = None;
+        self.undo_request = None;
+        self.updated_at = now;
+    }
+
+    /// Get the account holding a seat, if it has been taken
+    pub fn account_for(&self, player: Player) -> Option<AccountOwner> {
+        match player {
+            Player::X => Some(self.player_x),
+            Player::O => self.player_o,
+        }
+    }
+
+    /// Check if the game has ended in a win or draw
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status, GameStatus::Won(_) | GameStatus::Draw(_))
+    }
+
+    /// Get the seat held by an account in this game, if any
+    pub fn player_for(&self, owner: &AccountOwner) -> Option<Player> {
+        if &self.player_x == owner {
+            Some(Player::X)
+        } else if self.player_o.as_ref() == Some(owner) {
+            Some(Player::O)
+        } else {
+            None
+        }
+    }
+
+    /// Get the seat held by an account given in its canonical string form, if any
+    pub fn player_for_str(&self, owner: &str) -> Option<Player> {
+        if self.player_x.to_string() == owner {
+            Some(Player::X)
+        } else if self.player_o.as_ref().map(|p| p.to_string()).as_deref() == Some(owner) {
+            Some(Player::O)
+        } else {
+            None
+        }
+    }
+
+    /// Make a move on the board
+    pub fn make_move(
+        &mut self,
+        player: &AccountOwner,
+        row: usize,
+        col: usize,
+        now: Timestamp,
+    ) -> Result<(), GameError> {
+        // Validate game state
+        if self.status != GameStatus::InProgress {
+            return Err(GameError::NotInProgress);
+        }
+
+        // Validate player turn
+        let current_player_account = match self.current_player {
+            Player::X => &self.player_x,
+            Player::O => self.player_o.as_ref().ok_or(GameError::PlayerNotJoined)?,
+        };
+
+        if player != current_player_account {
+            return Err(GameError::NotYourTurn);
+        }
+
+        // A move after the deadline loses on time, before the position is even looked at
+        if self.is_move_overdue(now) || self.is_out_of_time(now) {
+            self.status = GameStatus::Won(self.current_player.opponent());
+            self.draw_offer = None;
+            self.undo_request = None;
+            self.updated_at = now;
+            return Err(GameError::TimedOut);
+        }
+
+        // Validate move position
+        if row >= self.size || col >= self.size {
+            return Err(GameError::OutOfBounds);
+        }
+
+        if self.board[row][col].is_some() {
+            return Err(GameError::PositionOccupied);
+        }
+
+        self.place_mark(row, col, now);
+        Ok(())
+    }
+
+    /// In a strict game, award the game to the opponent of a player whose move was
+    /// rejected with `error`, returning whether the game was forfeited
+    pub fn forfeit_illegal_move(
+        &mut self,
+        player: &AccountOwner,
+        error: &GameError,
+        now: Timestamp,
+    ) -> bool {
+        let illegal = matches!(
+            error,
+            GameError::NotYourTurn | GameError::OutOfBounds | GameError::PositionOccupied
+        );
+        let Some(offender) = self.player_for(player) else {
+            return false;
+        };
+        if !self.strict || !illegal || self.status != GameStatus::InProgress {
+            return false;
+        }
+
+        self.status = GameStatus::Won(offender.opponent());
+        self.draw_offer = None;
+        self.undo_request = None;
+        self.updated_at = now;
+        true
+    }
+
+    /// Check if the current player could place a mark at (row, col)
+    pub fn is_legal_move(&self, row: usize, col: usize) -> bool {
+        self.status == GameStatus::InProgress
+            && row < self.size
+            && col < self.size
+            && self.board[row][col].is_none()
+    }
+
+    /// Get every cell the current player could place a mark on
+    pub fn legal_moves(&self) -> Vec<(usize, usize)> {
+        let mut moves = Vec::new();
+        for row in 0..self.size {
+            for col in 0..self.size {
+                if self.is_legal_move(row, col) {
+                    moves.push((row, col));
+                }
+            }
+        }
+        moves
+    }
+
+    /// Render the board as text, with `.` for empty cells
+    pub fn render_ascii(&self) -> String {
+        let divider = "-".repeat(self.size * 2 - 1);
+        self.board
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.map_or(".", |p| p.symbol()))
+                    .collect::<Vec<_>>()
+                    .join("|")
+            })
+            .collect::<Vec<_>>()
+            .join(&format!("\n{}\n", divider))
+    }
+
+    /// Render the board as a self-contained SVG image, with X drawn as two lines and O as a circle
+    pub fn render_svg(&self) -> String {
+        const CELL: usize = 60;
+        const PAD: usize = 12;
+        let side = self.size * CELL;
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{side}" height="{side}" viewBox="0 0 {side} {side}">"#
+        );
+        svg.push_str(&format!(r#"<rect width="{side}" height="{side}" fill="white"/>"#));
+        for line in 1..self.size {
+            let at = line * CELL;
+            svg.push_str(&format!(
+                r#"<line x1="{at}" y1="0" x2="{at}" y2="{side}" stroke="black" stroke-width="2"/><line x1="0" y1="{at}" x2="{side}" y2="{at}" stroke="black" stroke-width="2"/>"#
+            ));
+        }
+        for (row, cells) in self.board.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                let (left, top) = (col * CELL, row * CELL);
+                match cell {
+                    Some(Player::X) => {
+                        let (x1, y1, x2, y2) = (left + PAD, top + PAD, left + CELL - PAD, top + CELL - PAD);
+                        svg.push_str(&format!(
+                            r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="crimson" stroke-width="4"/><line x1="{x2}" y1="{y1}" x2="{x1}" y2="{y2}" stroke="crimson" stroke-width="4"/>"#
+                        ));
+                    }
+                    Some(Player::O) => {
+                        let (cx, cy, r) = (left + CELL / 2, top + CELL / 2, CELL / 2 - PAD);
+                        svg.push_str(&format!(
+                            r#"<circle cx="{cx}" cy="{cy}" r="{r}" fill="none" stroke="royalblue" stroke-width="4"/>"#
+                        ));
+                    }
+                    None => {}
+                }
+            }
+        }
+        svg.push_str("</svg>");
+        svg
+    }
+
+    /// Encode the board and the side to move as a compact code, like "X1O/2X/O2:O"
+    ///
+    /// Each row lists its marks with runs of empty cells written as numbers,
+    /// rows are separated by '/', and the suffix names the player to move. A
+    /// win length other than the board size is appended as a final ":N".
+    pub fn position_code(&self) -> String {
+        let rows = self
+            .board
+            .iter()
+            .map(|row| {
+                let mut encoded = String::new();
+                let mut empty = 0;
+                for cell in row {
+                    match cell {
+                        Some(player) => {
+                            if empty > 0 {
+                                encoded.push_str(&empty.to_string());
+                                empty = 0;
+                            }
+                            encoded.push_str(player.symbol());
+                        }
+                        None => empty += 1,
+                    }
+                }
+                if empty > 0 {
+                    encoded.push_str(&empty.to_string());
+                }
+                encoded
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let mut code = format!("{}:{}", rows, self.current_player.symbol());
+        if self.win_length != self.size {
+            code.push_str(&format!(":{}", self.win_length));
+        }
+        code
+    }
+
+    /// Play a sequence of the caller's moves in a bot game, with the bot answering each one
+    ///
+    /// The moves are all the caller's own; the bot's replies are chosen as for
+    /// `bot_move`, so the caller never picks them. Stops at the first illegal move,
+    /// keeping the ones already made, and returns the moves applied as (player, row, col).
+    pub fn make_moves(
+        &mut self,
+        caller: &AccountOwner,
+        moves: &[(usize, usize)],
+        now: Timestamp,
+    ) -> Result<Vec<(AccountOwner, usize, usize)>, GameError> {
+        if !self.bot {
+            return Err(GameError::NotABotGame);
+        }
+        if self.player_for(caller).is_none() {
+            return Err(GameError::NotAParticipant);
+        }
+
+        // The bot may hold the opening move
+        let mut applied: Vec<_> = self.play_bot_reply(caller, now).into_iter().collect();
+        for &(row, col) in moves {
+            if self.make_move(caller, row, col, now).is_err() {
+                break;
+            }
+            applied.push((*caller, row, col));
+            applied.extend(self.play_bot_reply(caller, now));
+        }
+        Ok(applied)
+    }
+
+    /// Make the bot's move if the seat to move is not the caller's, returning it as (player, row, col)
+    fn play_bot_reply(
+        &mut self,
+        caller: &AccountOwner,
+        now: Timestamp,
+    ) -> Option<(AccountOwner, usize, usize)> {
+        let bot = self
+            .account_for(self.current_player)
+            .filter(|seat| seat != caller)?;
+        let (row, col) = self.bot_move(&bot).ok()?;
+        self.make_move(&bot, row, col, now).ok()?;
+        Some((bot, row, col))
+    }
+
+    /// Apply a move relayed from the chain hosting this game to a local mirror
+    ///
+    /// The host chain has already validated the move, so only the checks needed
+    /// to keep the mirror consistent are performed; an occupied cell means the
+    /// move was already applied, and a move arriving after the game was decided
+    /// is a late message that must not reopen it.
+    pub fn apply_remote_move(
+        &mut self,
+        player: AccountOwner,
+        row: usize,
+        col: usize,
+        now: Timestamp,
+    ) -> Result<(), GameError> {
+        if self.is_finished() {
+            return Err(GameError::NotInProgress);
+        }
+
+        if row >= self.size || col >= self.size {
+            return Err(GameError::OutOfBounds);
+        }
+
+        if self.board[row][col].is_some() {
+            return Err(GameError::PositionOccupied);
+        }
+
+        let seat = match self.player_for(&player) {
+            Some(seat) => seat,
+            None if self.player_o.is_none() => {
+                self.player_o = Some(player);
+                Player::O
+            }
+            None => return Err(GameError::NotAParticipant),
+        };
+
+        self.status = GameStatus::InProgress;
+        self.current_player = seat;
+        self.place_mark(row, col, now);
+        Ok(())
+    }
+
+    /// Place the current player's mark and update the game status
+    fn place_mark(&mut self, row: usize, col: usize, now: Timestamp) {
+        debug_assert!(self.has_square_board(), "board does not match its size");
+        // Make the move
+        self.charge_clock(now);
+        self.board[row][col] = Some(self.current_player);
+        self.moves.push((self.current_player, row, col));
+        self.move_times.push(now);
+        self.move_count += 1;
+        self.undo_request = None;
+        self.last_draw_offer_at = None;
+        // Playing on withdraws a pending resignation
+        if self.pending_resign.is_some_and(|(resigner, _)| resigner == self.current_player) {
+            self.pending_resign = None;
+        }
+        self.last_move_time = now;
+        self.updated_at = now;
+
+        // Check for win or draw
+        if let Some((winner, line)) = self.check_winner_around(row, col) {
+            self.status = GameStatus::Won(winner);
+            self.winning_line = Some(line);
+        } else if self.is_board_full() {
+            self.status = GameStatus::Draw(DrawReason::BoardFull);
+        } else {
+            // Switch turns
+            self.current_player = self.current_player.opponent();
+        }
+    }
+
+    /// Remove the last move from the board and hand the turn back to its player
+    fn take_back_last_move(&mut self, now: Timestamp) -> Result<(), GameError> {
+        let (mover, row, col) = self.moves.pop().ok_or(GameError::NoMovesToUndo)?;
+        self.move_times.pop();
+        self.board[row][col] = None;
+        self.move_count -= 1;
+        self.current_player = mover;
+        self.status = GameStatus::InProgress;
+        self.winning_line = None;
+        self.draw_offer = None;
+        self.undo_request = None;
+        self.pending_resign = None;
+        self.rematch_requests.clear();
+        self.delete_acks.clear();
+        self.last_move_time = now;
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Check if there's a winner anywhere on the board, returning the winner and the cells of the winning run
+    fn check_winner(&self) -> Option<(Player, Vec<(usize, usize)>)> {
+        for row in 0..self.size {
+            for col in 0..self.size {
+                if let Some(player) = self.board[row][col] {
+                    for direction in DIRECTIONS {
+                        if self.has_run(row, col, direction, player) {
+                            return Some((player, self.run_cells(row, col, direction)));
+                        }
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Check for a winning run through (row, col), which is all a single new mark can complete
+    ///
+    /// This inspects at most `win_length` cells each way along the four lines through
+    /// the cell instead of the whole board.
+    fn check_winner_around(&self, row: usize, col: usize) -> Option<(Player, Vec<(usize, usize)>)> {
+        let player = self.board[row][col]?;
+        let reach = self.win_length as isize - 1;
+        for (dr, dc) in DIRECTIONS {
+            let (row, col) = (row as isize, col as isize);
+            let backward = (1..=reach)
+                .take_while(|&k| self.cell_at(row - dr * k, col - dc * k) == Some(player))
+                .count() as isize;
+            let forward = (1..=reach)
+                .take_while(|&k| self.cell_at(row + dr * k, col + dc * k) == Some(player))
+                .count() as isize;
+            if backward + forward >= reach {
+                let start = ((row - dr * backward) as usize, (col - dc * backward) as usize);
+                return Some((player, self.run_cells(start.0, start.1, (dr, dc))));
+            }
+        }
+
+        None
+    }
+
+    /// Get the `win_length` cells starting at (row, col) in a direction
+    fn run_cells(&self, row: usize, col: usize, (dr, dc): (isize, isize)) -> Vec<(usize, usize)> {
+        (0..self.win_length as isize)
+            .map(|k| {
+                (
+                    (row as isize + dr * k) as usize,
+                    (col as isize + dc * k) as usize,
+                )
+            })
+            .collect()
+    }
+
+    /// Check if `player` holds `win_length` cells starting at (row, col) in a direction
+    fn has_run(&self, row: usize, col: usize, (dr, dc): (isize, isize), player: Player) -> bool {
+        (0..self.win_length as isize)
+            .all(|k| self.cell_at(row as isize + dr * k, col as isize + dc * k) == Some(player))
+    }
+
+    /// Get the mark at a possibly out-of-bounds position
+    fn cell_at(&self, row: isize, col: isize) -> Option<Player> {
+        if row < 0 || col < 0 || row as usize >= self.size || col as usize >= self.size {
+            return None;
+        }
+        self.board[row as usize][col as usize]
+    }
+
+    /// Get every empty cell on the board, regardless of the game status
+    fn empty_cells(&self) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        for row in 0..self.size {
+            for col in 0..self.size {
+                if self.board[row][col].is_none() {
+                    cells.push((row, col));
+                }
+            }
+        }
+        cells
+    }
+
+    /// Hash the board so that positions equal up to rotation or reflection share a hash
+    ///
+    /// Each of the eight symmetries is hashed by reading the board through a
+    /// transformed index, so no rotated or mirrored copy is ever built.
+    pub fn canonical_hash(&self) -> u64 {
+        let last = self.size - 1;
+        // Whether to transpose, flip the rows and flip the columns, covering every symmetry
+        let symmetries = (0..8).map(|bits| (bits & 1 != 0, bits & 2 != 0, bits & 4 != 0));
+        symmetries
+            .map(|(transpose, flip_rows, flip_cols)| {
+                let cells = (0..self.size)
+                    .flat_map(|row| (0..self.size).map(move |col| (row, col)));
+                fnv1a_hash_iter(cells.map(|(row, col)| {
+                    let (row, col) = if transpose { (col, row) } else { (row, col) };
+                    let row = if flip_rows { last - row } else { row };
+                    let col = if flip_cols { last - col } else { col };
+                    match self.board[row][col] {
+                        None => 0,
+                        Some(Player::X) => 1,
+                        Some(Player::O) => 2,
+                    }
+                }))
+            })
+            .min()
+            .expect("A board has eight symmetries")
+    }
+
+    /// Check that the board has exactly `size` rows of `size` cells
+    pub fn has_square_board(&self) -> bool {
+        self.board.len() == self.size && self.board.iter().all(|row| row.len() == self.size)
+    }
+
+    /// Check that the stored game is consistent with the rules
+    pub fn validate(&self) -> Result<(), GameError> {
+        let corrupt = |reason: &str| Err(GameError::CorruptGame(reason.to_string()));
+
+        if !self.has_square_board() {
+            return corrupt("board does not match its size");
+        }
+
+        // Handicap stones are placed freely; the players alternate after them
+        let mut first_count = 0;
+        let mut second_count = 0;
+        for &(player, _, _) in self.moves.iter().skip(self.handicap_count as usize) {
+            if player == self.first_player {
+                first_count += 1;
+            } else {
+                second_count += 1;
+            }
+        }
+        if first_count != second_count && first_count != second_count + 1 {
+            return corrupt("the first player must have as many marks as the second or one more");
+        }
+        let marks = self.board.iter().flatten().filter(|cell| cell.is_some()).count();
+        if marks != self.moves.len() || self.moves.len() != self.move_count as usize {
+            return corrupt("marks on the board do not match the move history");
+        }
+        if self.move_times.len() != self.moves.len() {
+            return corrupt("move times do not match the move history");
+        }
+        let move_on_board = |&(player, row, col): &(Player, usize, usize)| {
+            self.board.get(row).and_then(|cells| cells.get(col)) == Some(&Some(player))
+        };
+        if !self.moves.iter().all(move_on_board) {
+            return corrupt("marks on the board do not match the move history");
+        }
+        let mut played = HashSet::new();
+        if !self.moves.iter().all(|&(_, row, col)| played.insert((row, col))) {
+            return corrupt("a cell was played more than once");
+        }
+
+        if self.status != GameStatus::WaitingForPlayer && self.player_o.is_none() {
+            return corrupt("game has started without player O");
+        }
+        if self.player_o == Some(self.player_x) {
+            return corrupt("both seats are held by the same account");
+        }
+
+        // Once the game is over the turn no longer moves on, so it only matters before that
+        if matches!(self.status, GameStatus::WaitingForPlayer | GameStatus::InProgress) {
+            let to_move = if first_count == second_count {
+                self.first_player
+            } else {
+                self.first_player.opponent()
+            };
+            if self.current_player != to_move {
+                return corrupt("the player to move does not follow from the move history");
+            }
+        }
+
+        // A run on the board must have ended the game in its owner's favour, and be recorded
+        if let Some((winner, _)) = self.check_winner() {
+            if self.status != GameStatus::Won(winner) {
+                return corrupt("board has a winning run but the game was not won by it");
+            }
+            if self.winning_line.is_none() {
+                return corrupt("game was won on the board without recording its winning line");
+            }
+        }
+        if let Some(line) = &self.winning_line {
+            let GameStatus::Won(winner) = self.status else {
+                return corrupt("winning line recorded for a game that was not won");
+            };
+            let holds_winner = |&(row, col): &(usize, usize)| {
+                self.board.get(row).and_then(|cells| cells.get(col)) == Some(&Some(winner))
+            };
+            if !line.iter().all(holds_winner) {
+                return corrupt("winning line does not hold the winner's marks");
+            }
+            if !self.is_straight_run(line) {
+                return corrupt("winning line is not a straight run of the winning length");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check if `cells` are `win_length` adjacent cells along one of the winning directions
+    fn is_straight_run(&self, cells: &[(usize, usize)]) -> bool {
+        if cells.len() != self.win_length || cells.len() < 2 {
+            return false;
+        }
+        let step = |(row, col): (usize, usize), (next_row, next_col): (usize, usize)| {
+            (next_row as isize - row as isize, next_col as isize - col as isize)
+        };
+        let direction = step(cells[0], cells[1]);
+        let forwards = DIRECTIONS.contains(&direction);
+        let backwards = DIRECTIONS.contains(&(-direction.0, -direction.1));
+        (forwards || backwards) && cells.windows(2).all(|pair| step(pair[0], pair[1]) == direction)
+    }
+
+    /// Check if the board is full
+    pub fn is_board_full(&self) -> bool {
+        for row in 0..self.size {
+            for col in 0..self.size {
+                if self.board[row][col].is_none() {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Version of the portable game format written by `GameExport`
+pub const EXPORT_VERSION: u32 = 1;
+
+/// A game in the stable, versioned format used to export and import games
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GameExport {
+    pub version: u32,
+    pub player_x: String,
+    pub player_o: Option<String>,
+    pub size: usize,
+    pub win_length: usize,
+    /// One string per row, with "X", "O" or "." for each cell
+    pub board: Vec<String>,
+    pub moves: Vec<ExportedMove>,
+    pub first_player: String,
+    /// Number of moves at the start of `moves` that are handicap stones
+    #[serde(default)]
+    pub handicap_count: u32,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub current_player: String,
+    /// One of "waiting", "in_progress", "won_x", "won_o", "draw", "draw_agreement",
+    /// "draw_timeout" or "draw_dead"
+    pub status: String,
+    pub move_deadline_secs: u64,
+    /// Microseconds since the epoch
+    pub created_at: u64,
+    /// Microseconds since the epoch
+    pub updated_at: u64,
+    /// Microseconds since the epoch
+    pub last_move_time: u64,
+}
+
+/// A single move in an exported game
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ExportedMove {
+    pub player: String,
+    pub row: usize,
+    pub col: usize,
+    /// Microseconds since the epoch when the move was made
+    #[serde(default)]
+    pub at: u64,
+}
+
+impl From<&Game> for GameExport {
+    fn from(game: &Game) -> Self {
+        GameExport {
+            version: EXPORT_VERSION,
+            player_x: game.player_x.to_string(),
+            player_o: game.player_o.map(|owner| owner.to_string()),
+            size: game.size,
+            win_length: game.win_length,
+            board: game
+                .board
+                .iter()
+                .map(|row| row.iter().map(|cell| cell.map_or(".", |p| p.symbol())).collect())
+                .collect(),
+            moves: game
+                .moves
+                .iter()
+                .zip(&game.move_times)
+                .map(|(&(player, row, col), at)| ExportedMove {
+                    player: player.symbol().to_string(),
+                    row,
+                    col,
+                    at: at.micros(),
+                })
+                .collect(),
+            first_player: game.first_player.symbol().to_string(),
+            handicap_count: game.handicap_count,
+            title: game.title.clone(),
+            description: game.description.clone(),
+            current_player: game.current_player.symbol().to_string(),
+            status: match game.status {
+                GameStatus::WaitingForPlayer => "waiting",
+                GameStatus::InProgress => "in_progress",
+                GameStatus::Won(Player::X) => "won_x",
+                GameStatus::Won(Player::O) => "won_o",
+                GameStatus::Draw(DrawReason::BoardFull) => "draw",
+                GameStatus::Draw(DrawReason::Agreement) => "draw_agreement",
+                GameStatus::Draw(DrawReason::Timeout) => "draw_timeout",
+                GameStatus::Draw(DrawReason::DeadPosition) => "draw_dead",
+            }
+            .to_string(),
+            move_deadline_secs: game.move_deadline_secs,
+            created_at: game.created_at.micros(),
+            updated_at: game.updated_at.micros(),
+            last_move_time: game.last_move_time.micros(),
+        }
+    }
+}
+
+impl GameExport {
+    /// Rebuild a game hosted on `chain_id`, checking it against the rules
+    pub fn into_game(self, chain_id: ChainId) -> Result<Game, GameError> {
+        let invalid = |reason: &str| GameError::InvalidImport(reason.to_string());
+
+        if self.version != EXPORT_VERSION {
+            return Err(invalid("unsupported version"));
+        }
+        let owner = |owner: &str| {
+            owner
+                .parse::<AccountOwner>()
+                .map_err(|_| invalid("invalid player"))
+        };
+        let player = |symbol: &str| match symbol {
+            "X" => Ok(Player::X),
+            "O" => Ok(Player::O),
+            _ => Err(invalid("invalid seat")),
+        };
+
+        let mut game = Game::new_custom(
+            owner(&self.player_x)?,
+            chain_id,
+            self.size,
+            self.win_length,
+            Timestamp::from(self.created_at),
+        )?
+        .with_move_deadline(self.move_deadline_secs)?
+        .with_first_player(player(&self.first_player)?)
+        .with_details(self.title, self.description)?;
+        game.player_o = self.player_o.as_deref().map(owner).transpose()?;
+        game.current_player = player(&self.current_player)?;
+        game.status = match self.status.as_str() {
+            "waiting" => GameStatus::WaitingForPlayer,
+            "in_progress" => GameStatus::InProgress,
+            "won_x" => GameStatus::Won(Player::X),
+            "won_o" => GameStatus::Won(Player::O),
+            "draw" => GameStatus::Draw(DrawReason::BoardFull),
+            "draw_agreement" => GameStatus::Draw(DrawReason::Agreement),
+            "draw_timeout" => GameStatus::Draw(DrawReason::Timeout),
+            "draw_dead" => GameStatus::Draw(DrawReason::DeadPosition),
+            _ => return Err(invalid("invalid status")),
+        };
+        // A result is only credible when it was reached on this chain, so finished games stay out
+        if game.is_finished() {
+            return Err(invalid("finished games cannot be imported"));
+        }
+
+        if self.board.len() != self.size {
+            return Err(invalid("board does not match its size"));
+        }
+        for (row, cells) in self.board.iter().enumerate() {
+            if cells.chars().count() != self.size {
+                return Err(invalid("board does not match its size"));
+            }
+            for (col, cell) in cells.chars().enumerate() {
+                game.board[row][col] = match cell {
+                    '.' => None,
+                    'X' => Some(Player::X),
+                    'O' => Some(Player::O),
+                    _ => return Err(invalid("invalid cell")),
+                };
+            }
+        }
+        for exported in &self.moves {
+            game.moves.push((player(&exported.player)?, exported.row, exported.col));
+            game.move_times.push(Timestamp::from(exported.at));
+        }
+        game.move_count = u32::try_from(game.moves.len()).map_err(|_| invalid("too many moves"))?;
+        if self.handicap_count > game.move_count {
+            return Err(invalid("more handicap stones than moves"));
+        }
+        game.handicap_count = self.handicap_count;
+        if let GameStatus::Won(winner) = game.status {
+            game.winning_line = game
+                .check_winner()
+                .filter(|(player, _)| *player == winner)
+                .map(|(_, line)| line);
+        }
+        game.updated_at = Timestamp::from(self.updated_at);
+        game.last_move_time = Timestamp::from(self.last_move_time);
+
+        game.validate()?;
+        Ok(game)
+    }
+}
+
+/// A board position read from a position code
+pub struct Position {
+    pub board: Vec<Vec<Option<Player>>>,
+    pub to_move: Player,
+    pub win_length: usize,
+}
+
+/// Read a position code written by `Game::position_code`
+pub fn decode_position(code: &str) -> Result<Position, GameError> {
+    let mut parts = code.split(':');
+    let rows = parts.next().ok_or(GameError::InvalidPosition)?;
+    let to_move = match parts.next() {
+        Some("X") => Player::X,
+        Some("O") => Player::O,
+        _ => return Err(GameError::InvalidPosition),
+    };
+
+    let mut board = Vec::new();
+    for row in rows.split('/') {
+        let mut cells = Vec::new();
+        let mut empty = String::new();
+        for symbol in row.chars().chain(std::iter::once('/')) {
+            if symbol.is_ascii_digit() {
+                empty.push(symbol);
+                continue;
+            }
+            if !empty.is_empty() {
+                let count = empty.parse::<usize>().map_err(|_| GameError::InvalidPosition)?;
+                if count == 0 || cells.len() + count > MAX_BOARD_SIZE {
+                    return Err(GameError::InvalidPosition);
+                }
+                cells.extend(std::iter::repeat(None).take(count));
+                empty.clear();
+            }
+            match symbol {
+                'X' => cells.push(Some(Player::X)),
+                'O' => cells.push(Some(Player::O)),
+                '/' => {}
+                _ => return Err(GameError::InvalidPosition),
+            }
+        }
+        board.push(cells);
+    }
+
+    let size = board.len();
+    if board.iter().any(|cells| cells.len() != size) {
+        return Err(GameError::InvalidPosition);
+    }
+    let win_length = match parts.next() {
+        Some(win_length) => win_length.parse().map_err(|_| GameError::InvalidPosition)?,
+        None => size,
+    };
+    if parts.next().is_some() {
+        return Err(GameError::InvalidPosition);
+    }
+
+    Ok(Position {
+        board,
+        to_move,
+        win_length,
+    })
+}
+
+/// Choose a move for `player` at the given bot difficulty
+///
+/// The random choices of easier bots come from the game's `rng_seed` advanced
+/// by the number of moves made, so a game replays identically from its seed.
+pub fn choose_bot_move(game: &Game, player: Player, difficulty: BotDifficulty) -> Option<(usize, usize)> {
+    match difficulty {
+        BotDifficulty::Easy => random_move(game),
+        BotDifficulty::Medium => winning_move(game, player)
+            .or_else(|| winning_move(game, player.opponent()))
+            .or_else(|| random_move(game)),
+        BotDifficulty::Hard => minimax_move(game, player),
+    }
+}
+
+/// Find a cell that completes a winning run for `player`, if any
+fn winning_move(game: &Game, player: Player) -> Option<(usize, usize)> {
+    let mut scratch = game.clone();
+    scratch.empty_cells().into_iter().find(|&(row, col)| {
+        scratch.board[row][col] = Some(player);
+        let wins = scratch.check_winner_around(row, col).is_some();
+        scratch.board[row][col] = None;
+        wins
+    })
+}
+
+/// Increment between successive states of the SplitMix64 generator
+const SPLITMIX64_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Get the output of the SplitMix64 generator for a state
+///
+/// Small and deterministic, so bot games replay identically on every validator
+/// without an external random number generator.
+fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(SPLITMIX64_GAMMA);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Pick an empty cell pseudo-randomly, using the game's seed advanced once per move made
+fn random_move(game: &Game) -> Option<(usize, usize)> {
+    let cells = game.empty_cells();
+    if cells.is_empty() {
+        return None;
+    }
+    let state = game
+        .rng_seed
+        .wrapping_add(u64::from(game.move_count).wrapping_mul(SPLITMIX64_GAMMA));
+    let index = (splitmix64(state) % cells.len() as u64) as usize;
+    Some(cells[index])
+}
+
+/// Boards with at most this many empty cells are searched to the end of the game
+const FULL_SEARCH_EMPTY_CELLS: usize = 9;
+/// Plies searched on boards with more empty cells than a full search allows
+const SHALLOW_SEARCH_DEPTH: usize = 2;
+
+/// Pick the best move for `player` using a minimax search
+///
+/// Positions with few enough empty cells (every 3x3 position) are searched
+/// exhaustively, so the chosen move never loses a game that can be saved.
+/// Larger positions only look a couple of plies ahead, and only at cells next
+/// to a mark, which keeps the search affordable on boards up to 19x19.
+pub fn minimax_move(game: &Game, player: Player) -> Option<(usize, usize)> {
+    let mut scratch = game.clone();
+    let full_search = scratch.empty_cells().len() <= FULL_SEARCH_EMPTY_CELLS;
+    let depth_limit = if full_search {
+        usize::MAX
+    } else {
+        SHALLOW_SEARCH_DEPTH
+    };
+
+    let mut table = HashMap::new();
+    let mut best = None;
+    let mut best_score = i32::MIN;
+    for (row, col) in search_candidates(&scratch, full_search) {
+        scratch.board[row][col] = Some(player);
+        let score = -negamax(
+            &mut scratch,
+            &mut table,
+            player.opponent(),
+            Some((row, col)),
+            1,
+            depth_limit,
+            -i32::MAX,
+            i32::MAX,
+        );
+        scratch.board[row][col] = None;
+        if score > best_score {
+            best_score = score;
+            best = Some((row, col));
+        }
+    }
+    best
+}
+
+/// Find how an in-progress game ends with perfect play from both sides
+///
+/// Returns `None` once the position has more empty cells than a full search
+/// allows, or if the game is not in progress.
+pub fn perfect_play_result(game: &Game) -> Option<GameResult> {
+    if game.status != GameStatus::InProgress || game.empty_cells().len() > FULL_SEARCH_EMPTY_CELLS {
+        return None;
+    }
+
+    let to_move = game.current_player;
+    let mut scratch = game.clone();
+    let mut table = HashMap::new();
+    let score = negamax(
+        &mut scratch,
+        &mut table,
+        to_move,
+        None,
+        0,
+        usize::MAX,
+        -i32::MAX,
+        i32::MAX,
+    );
+    Some(match score.signum() {
+        1 => GameResult::WonBy(to_move),
+        -1 => GameResult::WonBy(to_move.opponent()),
+        _ => GameResult::Draw(DrawReason::BoardFull),
+    })
+}
+
+/// How a score stored in the transposition table relates to the true score
+#[derive(Clone, Copy)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+/// Get the cells a search tries next: every empty cell in a full search, otherwise
+/// only the empty cells next to a mark, or the centre of an empty board
+fn search_candidates(game: &Game, full_search: bool) -> Vec<(usize, usize)> {
+    let cells = game.empty_cells();
+    if full_search {
+        return cells;
+    }
+    let last = game.size - 1;
+    let next_to_mark = |&(row, col): &(usize, usize)| {
+        let cols = col.saturating_sub(1)..=(col + 1).min(last);
+        (row.saturating_sub(1)..=(row + 1).min(last))
+            .any(|r| cols.clone().any(|c| game.board[r][c].is_some()))
+    };
+    let nearby: Vec<_> = cells.iter().copied().filter(next_to_mark).collect();
+    let centre = (game.size / 2, game.size / 2);
+    if !nearby.is_empty() {
+        nearby
+    } else if game.board[centre.0][centre.1].is_none() {
+        vec![centre]
+    } else {
+        cells
+    }
+}
+
+/// Score a position from the point of view of `to_move`, preferring quicker wins
+///
+/// `last_move` is the cell the opponent just played, the only place a new
+/// winning run can appear, so wins are found without scanning the board.
+/// Scores are cached in `table` under the board's canonical hash. Within one
+/// search a position's depth and side to move follow from its marks, so
+/// symmetric positions always share a score.
+#[allow(clippy::too_many_arguments)]
+fn negamax(
+    game: &mut Game,
+    table: &mut HashMap<u64, (i32, Bound)>,
+    to_move: Player,
+    last_move: Option<(usize, usize)>,
+    depth: usize,
+    depth_limit: usize,
+    mut alpha: i32,
+    mut beta: i32,
+) -> i32 {
+    if let Some((row, col)) = last_move {
+        if game.check_winner_around(row, col).is_some() {
+            return -(1000 - depth as i32);
+        }
+    }
+    if depth >= depth_limit || game.is_board_full() {
+        return 0;
+    }
+
+    let key = game.canonical_hash();
+    if let Some(&(score, bound)) = table.get(&key) {
+        match bound {
+            Bound::Exact => return score,
+            Bound::Lower => alpha = alpha.max(score),
+            Bound::Upper => beta = beta.min(score),
+        }
+        if alpha >= beta {
+            return score;
+        }
+    }
+
+    let original_alpha = alpha;
+    let mut best = -i32::MAX;
+    for (row, col) in search_candidates(game, depth_limit == usize::MAX) {
+        game.board[row][col] = Some(to_move);
+        let score = -negamax(
+            game,
+            table,
+            to_move.opponent(),
+            Some((row, col)),
+            depth + 1,
+            depth_limit,
+            -beta,
+            -alpha,
+        );
+        game.board[row][col] = None;
+        best = best.max(score);
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    let bound = if best <= original_alpha {
+        Bound::Upper
+    } else if best >= beta {
+        Bound::Lower
+    } else {
+        Bound::Exact
+    };
+    table.insert(key, (best, bound));
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_sdk::{
+        base::{CryptoHash, Owner},
+        util::BlockingWait,
+        views::{KeyValueStore, View},
+    };
+
+    use super::*;
+
+    fn account(name: &str) -> AccountOwner {
+        AccountOwner::User(Owner(CryptoHash::test_hash(name)))
+    }
+
+    /// Load an empty application state backed by an in-memory store
+    fn empty_state() -> TicTacToeState {
+        let store = KeyValueStore::mock().to_mut();
+        let context = ViewStorageContext::new_unsafe(store, Vec::new(), ());
+        TicTacToeState::load(context)
+            .blocking_wait()
+            .expect("Failed to load state from the mock store")
+    }
+
+    fn game_with_board(board: Vec<Vec<Option<Player>>>) -> Game {
+        let size = board.len();
+        let mut game = Game::new_sized(account("x"), ChainId::root(0), size, Timestamp::from(0))
+            .expect("valid board size");
+        game.board = board;
+        game
+    }
+
+    /// An asymmetric 4x4 position, so every symmetry gives a different board
+    fn lopsided_board() -> Vec<Vec<Option<Player>>> {
+        let mut board = vec![vec![None; 4]; 4];
+        board[0][1] = Some(Player::X);
+        board[0][2] = Some(Player::X);
+        board[2][3] = Some(Player::O);
+        board[3][0] = Some(Player::O);
+        board
+    }
+
+    /// Rotate a square board a quarter turn clockwise
+    fn rotate_board(board: &[Vec<Option<Player>>]) -> Vec<Vec<Option<Player>>> {
+        let size = board.len();
+        (0..size)
+            .map(|row| (0..size).map(|col| board[size - 1 - col][row]).collect())
+            .collect()
+    }
+
+    /// Mirror a board left to right
+    fn mirror_board(board: &[Vec<Option<Player>>]) -> Vec<Vec<Option<Player>>> {
+        board
+            .iter()
+            .map(|row| row.iter().rev().copied().collect())
+            .collect()
+    }
+
+    #[test]
+    fn creation_window_does_not_slide_with_each_creation() {
+        let mut record = CreationRecord::default();
+        let start = 1_000_000;
+        let window = CREATION_WINDOW_SECS * 1_000_000;
+        // Spread the allowed creations over the window, each one well within a window of the last
+        let step = window / (u64::from(MAX_GAMES_PER_WINDOW) + 1);
+        for n in 0..u64::from(MAX_GAMES_PER_WINDOW) {
+            record.record(Timestamp::from(start + n * step)).unwrap();
+        }
+        let next = Timestamp::from(start + u64::from(MAX_GAMES_PER_WINDOW) * step);
+        assert!(matches!(record.record(next), Err(GameError::RateLimited)));
+
+        let window_end = Timestamp::from(start + window);
+        record.record(window_end).unwrap();
+        assert_eq!(record.created_count, 1);
+        assert_eq!(record.window_start, window_end);
+    }
+
+    #[test]
+    fn host_and_mirror_reach_the_same_board() {
+        let (x, o) = (account("x"), account("o"));
+        let now = Timestamp::from(0);
+        let mut host = Game::new_between(x, o, ChainId::root(0), now).unwrap();
+        let mut mirror = host.clone();
+
+        for (player, row, col) in [(x, 0, 0), (o, 1, 1), (x, 0, 1), (o, 2, 2), (x, 0, 2)] {
+            host.make_move(&player, row, col, now).unwrap();
+            mirror.apply_remote_move(player, row, col, now).unwrap();
+            assert_eq!(mirror.board, host.board);
+        }
+        assert_eq!(host.status, GameStatus::Won(Player::X));
+        assert_eq!(mirror.status, host.status);
+        assert_eq!(mirror.moves, host.moves);
+        assert_eq!(mirror.winning_line, host.winning_line);
+    }
+
+    #[test]
+    fn late_moves_do_not_reopen_a_decided_mirror() {
+        let (x, o) = (account("x"), account("o"));
+        let now = Timestamp::from(0);
+        let mut mirror = Game::new_between(x, o, ChainId::root(0), now).unwrap();
+        for (player, row, col) in [(x, 0, 0), (o, 1, 1), (x, 0, 1), (o, 2, 2), (x, 0, 2)] {
+            mirror.apply_remote_move(player, row, col, now).unwrap();
+        }
+        let decided = mirror.clone();
+
+        // A replayed move and one that was overtaken by the winning move
+        for (player, row, col) in [(o, 1, 1), (o, 2, 0)] {
+            assert!(mirror.apply_remote_move(player, row, col, now).is_err());
+        }
+        assert_eq!(mirror.status, GameStatus::Won(Player::X));
+        assert_eq!(mirror.board, decided.board);
+        assert_eq!(mirror.moves, decided.moves);
+    }
+
+    #[test]
+    fn new_games_are_refused_once_the_id_nonce_runs_out() {
+        let (x, o) = (account("x"), account("o"));
+        let mut state = empty_state();
+        state.next_game_id.set(u64::MAX - 1);
+
+        // One ID is left, so a pair of games cannot be reserved
+        let result = state
+            .reserve_game_ids(ChainId::root(0), &[x, o])
+            .blocking_wait();
+        assert!(matches!(result, Err(GameError::IdSpaceExhausted)));
+        assert_eq!(*state.next_game_id.get(), u64::MAX - 1);
+
+        let mut reservation = state
+            .reserve_game_ids(ChainId::root(0), &[x])
+            .blocking_wait()
+            .unwrap();
+        let game = Game::new(x, ChainId::root(0), Timestamp::from(0));
+        let game_id = state.insert_reserved_game(&mut reservation, game);
+        assert!(state.games.contains_key(&game_id).blocking_wait().unwrap());
+        assert_eq!(*state.next_game_id.get(), u64::MAX);
+
+        let result = state
+            .reserve_game_ids(ChainId::root(0), &[x])
+            .blocking_wait();
+        assert!(matches!(result, Err(GameError::IdSpaceExhausted)));
+        assert_eq!(state.games.count().blocking_wait().unwrap(), 1);
+    }
+
+    #[test]
+    fn x_cannot_open_a_game_where_o_moves_first() {
+        let (x, o) = (account("x"), account("o"));
+        let now = Timestamp::from(0);
+        let mut game = Game::new_between(x, o, ChainId::root(0), now)
+            .unwrap()
+            .with_first_player(Player::O);
+
+        assert!(matches!(game.make_move(&x, 0, 0, now), Err(GameError::NotYourTurn)));
+        assert!(game.moves.is_empty());
+        game.make_move(&o, 0, 0, now).unwrap();
+        game.make_move(&x, 1, 1, now).unwrap();
+        game.validate().unwrap();
+    }
+
+    #[test]
+    fn resignation_reaches_the_mirror_once_finalized() {
+        let (x, o) = (account("x"), account("o"));
+        let start = Timestamp::from(0);
+        let mut host = Game::new_between(x, o, ChainId::root(0), start).unwrap();
+        let mut mirror = host.clone();
+        for (player, row, col) in [(x, 1, 1), (o, 0, 0)] {
+            host.make_move(&player, row, col, start).unwrap();
+            mirror.apply_remote_move(player, row, col, start).unwrap();
+        }
+
+        host.resign(&o, start).unwrap();
+        let in_grace = Timestamp::from(RESIGN_GRACE_SECS * 1_000_000);
+        assert!(matches!(
+            host.finalize_resign(in_grace),
+            Err(GameError::ResignationInGrace)
+        ));
+        assert_eq!(host.status, GameStatus::InProgress);
+
+        let after_grace = Timestamp::from((RESIGN_GRACE_SECS + 1) * 1_000_000);
+        let resigner = host.finalize_resign(after_grace).unwrap();
+        assert_eq!(resigner, Player::O);
+        assert_eq!(host.status, GameStatus::Won(Player::X));
+
+        // The mirror only hears about the resignation once the host has finalized it
+        assert_eq!(mirror.status, GameStatus::InProgress);
+        let resigner = host.account_for(resigner).unwrap();
+        let seat = mirror.player_for(&resigner).unwrap();
+        mirror.apply_remote_result(GameResult::WonBy(seat.opponent()), after_grace);
+        assert_eq!(mirror.status, host.status);
+        assert_eq!(mirror.result(), host.result());
+        mirror.validate().unwrap();
+    }
+
+    #[test]
+    fn local_and_full_win_checks_agree() {
+        let mut seed = 7;
+        for (size, win_length) in [(3, 3), (4, 3), (5, 4), (7, 5), (9, 5)] {
+            for _ in 0..200 {
+                let mut game = Game::new_custom(
+                    account("x"),
+                    ChainId::root(0),
+                    size,
+                    win_length,
+                    Timestamp::from(0),
+                )
+                .unwrap();
+                for row in 0..size {
+                    for col in 0..size {
+                        seed = splitmix64(seed);
+                        game.board[row][col] = match seed % 3 {
+                            0 => None,
+                            1 => Some(Player::X),
+                            _ => Some(Player::O),
+                        };
+                    }
+                }
+
+                let mut any_local_win = false;
+                for row in 0..size {
+                    for col in 0..size {
+                        if let Some((player, line)) = game.check_winner_around(row, col) {
+                            any_local_win = true;
+                            assert_eq!(game.board[row][col], Some(player));
+                            assert_eq!(line.len(), win_length);
+                            assert!(line.contains(&(row, col)));
+                            assert!(line.iter().all(|&(r, c)| game.board[r][c] == Some(player)));
+                        }
+                    }
+                }
+                assert_eq!(any_local_win, game.check_winner().is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn blocked_position_is_a_dead_draw_before_the_ninth_move() {
+        let (x, o) = (account("x"), account("o"));
+        let now = Timestamp::from(0);
+        let mut game = Game::new_between(x, o, ChainId::root(0), now).unwrap();
+        // X | O | X
+        // O | X | .
+        // O | X | O
+        let moves = [
+            (x, 0, 0),
+            (o, 0, 1),
+            (x, 0, 2),
+            (o, 1, 0),
+            (x, 1, 1),
+            (o, 2, 0),
+            (x, 2, 1),
+        ];
+        for (player, row, col) in moves {
+            game.make_move(&player, row, col, now).unwrap();
+        }
+        // The main diagonal is still open for X
+        assert!(!game.is_dead_draw());
+        assert!(matches!(
+            game.claim_dead_draw(&o, now),
+            Err(GameError::NotDeadDraw)
+        ));
+
+        game.make_move(&o, 2, 2, now).unwrap();
+        assert_eq!(game.status, GameStatus::InProgress);
+        assert!(game.is_dead_draw());
+        game.claim_dead_draw(&o, now).unwrap();
+        assert_eq!(game.status, GameStatus::Draw(DrawReason::DeadPosition));
+    }
+
+    #[test]
+    fn runs_needing_more_marks_than_moves_left_are_blocked() {
+        let (x, o) = (account("x"), account("o"));
+        let now = Timestamp::from(0);
+        let play = |moves: &[(AccountOwner, usize, usize)]| {
+            let mut game = Game::new_between(x, o, ChainId::root(0), now).unwrap();
+            for &(player, row, col) in moves {
+                game.make_move(&player, row, col, now).unwrap();
+            }
+            game
+        };
+
+        // X | O | X
+        // . | . | .
+        // O | X | O
+        // X has two moves left, one short of filling the middle row
+        let mut game = play(&[
+            (x, 0, 0),
+            (o, 0, 1),
+            (x, 0, 2),
+            (o, 2, 0),
+            (x, 2, 1),
+            (o, 2, 2),
+        ]);
+        assert_eq!(game.empty_cells().len(), 3);
+        assert!(game.is_dead_draw());
+        game.claim_dead_draw(&x, now).unwrap();
+        assert_eq!(game.status, GameStatus::Draw(DrawReason::DeadPosition));
+
+        // X | X | O
+        // O | O | X
+        // X | . | .
+        // X has one move left, one short of filling the bottom row
+        let game = play(&[
+            (x, 0, 0),
+            (o, 0, 2),
+            (x, 0, 1),
+            (o, 1, 0),
+            (x, 1, 2),
+            (o, 1, 1),
+            (x, 2, 0),
+        ]);
+        assert!(game.is_dead_draw());
+
+        // X | O | X
+        // . | . | O
+        // O | X | .
+        // The main diagonal is the only run left, and X has the two moves it needs
+        let mut game = play(&[
+            (x, 0, 0),
+            (o, 0, 1),
+            (x, 0, 2),
+            (o, 1, 2),
+            (x, 2, 1),
+            (o, 2, 0),
+        ]);
+        assert!(!game.is_dead_draw());
+        assert!(matches!(
+            game.claim_dead_draw(&o, now),
+            Err(GameError::NotDeadDraw)
+        ));
+    }
+
+    #[test]
+    fn eviction_prefers_the_oldest_finished_game() {
+        let (x, o) = (account("x"), account("o"));
+        let mut state = empty_state();
+        let stored = [
+            (0, GameStatus::Won(Player::X), 300),
+            (1, GameStatus::InProgress, 100),
+            (2, GameStatus::Draw(DrawReason::BoardFull), 200),
+            (3, GameStatus::WaitingForPlayer, 50),
+        ];
+        for (id, status, updated_at) in stored {
+            let mut game = Game::new_between(x, o, ChainId::root(0), Timestamp::from(0)).unwrap();
+            game.status = status;
+            game.updated_at = Timestamp::from(updated_at);
+            state.games.insert(&id, game).unwrap();
+        }
+
+        assert_eq!(state.game_to_evict(0).blocking_wait().unwrap(), None);
+        assert_eq!(state.game_to_evict(5).blocking_wait().unwrap(), None);
+        assert_eq!(state.game_to_evict(4).blocking_wait().unwrap(), Some(2));
+        // Room for several new games is made oldest first, and only from finished games
+        assert_eq!(
+            state.games_to_evict(4, 2).blocking_wait().unwrap(),
+            vec![2, 0]
+        );
+        assert!(matches!(
+            state.games_to_evict(4, 3).blocking_wait(),
+            Err(GameError::StorageFull)
+        ));
+
+        // Games still waiting or in progress are never evicted, however old
+        state.games.remove(&0).unwrap();
+        state.games.remove(&2).unwrap();
+        assert!(matches!(
+            state.game_to_evict(2).blocking_wait(),
+            Err(GameError::StorageFull)
+        ));
+    }
+
+    #[test]
+    fn cells_parse_with_multi_letter_columns() {
+        assert_eq!(parse_cell("a1", 3).unwrap(), (0, 0));
+        assert_eq!(parse_cell("C3", 3).unwrap(), (2, 2));
+        assert_eq!(parse_cell("s19", 19).unwrap(), (18, 18));
+        assert_eq!(parse_cell("z1", 30).unwrap(), (0, 25));
+        assert_eq!(parse_cell("aa2", 30).unwrap(), (1, 26));
+        assert_eq!(parse_cell("AD30", 30).unwrap(), (29, 29));
+    }
+
+    #[test]
+    fn cells_outside_the_board_or_malformed_are_refused() {
+        for (cell, size) in [
+            ("d1", 3),
+            ("a0", 3),
+            ("a4", 3),
+            ("ae1", 30),
+            ("a99999999999999999999999", 3),
+        ] {
+            assert!(
+                matches!(parse_cell(cell, size), Err(GameError::OutOfBounds)),
+                "{cell} on a {size}x{size} board"
+            );
+        }
+        for cell in ["", "a", "1a", "a1b", "a-1", "é1"] {
+            assert!(
+                matches!(parse_cell(cell, 3), Err(GameError::InvalidCell)),
+                "{cell}"
+            );
+        }
+    }
+
+    #[test]
+    fn batched_moves_leave_the_bot_replies_to_the_bot() {
+        let (x, bot) = (account("x"), account("bot"));
+        let now = Timestamp::from(0);
+        let mut game =
+            Game::new_vs_bot(x, bot, BotDifficulty::Easy, ChainId::root(0), now).unwrap();
+        // Alternating seats, this would have X choose O's replies and win along the top row
+        let moves = [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)];
+        let applied = game.make_moves(&x, &moves, now).unwrap();
+
+        assert!(!applied.is_empty());
+        for (index, &(player, row, col)) in applied.iter().enumerate() {
+            let (expected, seat) = if index % 2 == 0 {
+                (x, Player::X)
+            } else {
+                (bot, Player::O)
+            };
+            assert_eq!(player, expected);
+            assert_eq!(game.board[row][col], Some(seat));
+        }
+        let caller_moves: Vec<_> = applied
+            .iter()
+            .filter(|&&(player, _, _)| player == x)
+            .map(|&(_, row, col)| (row, col))
+            .collect();
+        assert_eq!(caller_moves[..], moves[..caller_moves.len()]);
+    }
+
+    #[test]
+    fn mirrors_rebuild_the_setup_of_the_host_game() {
+        let (x, o) = (account("x"), account("o"));
+        let now = Timestamp::from(0);
+        let handicap = Game::new_custom(x, ChainId::root(0), 5, 4, now)
+            .unwrap()
+            .with_handicap(&[(Player::X, 2, 2), (Player::X, 0, 4)])
+            .unwrap();
+        let mut played = Game::new_between(x, o, ChainId::root(0), now).unwrap();
+        played.make_move(&x, 1, 1, now).unwrap();
+        played.make_move(&o, 0, 0, now).unwrap();
+        played.make_move(&x, 2, 0, now).unwrap();
+        let position =
+            Game::from_position(x, ChainId::root(0), &played.position_code(), now).unwrap();
+        let sized = Game::new_custom(x, ChainId::root(0), 7, 5, now)
+            .unwrap()
+            .with_first_player(Player::O);
+
+        for host in [handicap, position, sized] {
+            let mirror = Game::from_setup(x, ChainId::root(1), &host.setup(), now).unwrap();
+            assert_eq!(mirror.size, host.size);
+            assert_eq!(mirror.win_length, host.win_length);
+            assert_eq!(mirror.board, host.board);
+            assert_eq!(mirror.moves, host.moves);
+            assert_eq!(mirror.handicap_count, host.handicap_count);
+            assert_eq!(mirror.first_player, host.first_player);
+            assert_eq!(mirror.current_player, host.current_player);
+        }
+    }
+
+    #[test]
+    fn named_opponents_skip_the_same_chain_check_but_joiners_do_not() {
+        let (x, o, other) = (account("x"), account("o"), account("other"));
+        let (host, elsewhere) = (ChainId::root(0), ChainId::root(1));
+        let now = Timestamp::from(0);
+
+        let mut open = Game::new(x, host, now);
+        assert!(matches!(
+            open.join(o, host, None, now),
+            Err(GameError::SameChain)
+        ));
+        open.join(o, elsewhere, None, now).unwrap();
+
+        // A named opponent is seated even though the game was set up on their chain
+        let mut named = Game::new_between(x, o, host, now).unwrap();
+        assert_eq!(named.player_o, Some(o));
+        assert!(!named.can_join_from(host));
+
+        // Once the seat is given up, taking it again goes through the check
+        named.leave(&o, now).unwrap();
+        assert!(matches!(
+            named.join(other, host, None, now),
+            Err(GameError::SameChain)
+        ));
+        named.join(other, elsewhere, None, now).unwrap();
+    }
+
+    /// Play a bot game to the end with both sides choosing Easy moves
+    fn easy_game(rng_seed: u64, created_at: u64) -> Vec<(usize, usize)> {
+        let now = Timestamp::from(created_at);
+        let mut game = Game::new_vs_bot(
+            account("x"),
+            account("bot"),
+            BotDifficulty::Easy,
+            ChainId::root(0),
+            now,
+        )
+        .expect("the bot is not the creator")
+        .with_rng_seed(rng_seed);
+        let mut played = Vec::new();
+        while game.status == GameStatus::InProgress {
+            let (row, col) = choose_bot_move(&game, game.current_player, BotDifficulty::Easy)
+                .expect("a game in progress has an empty cell");
+            let mover = game.account_for(game.current_player).expect("both seats are taken");
+            game.make_move(&mover, row, col, now).unwrap();
+            played.push((row, col));
+        }
+        played
+    }
+
+    #[test]
+    fn easy_bot_replays_the_same_moves_from_the_same_seed() {
+        let first = easy_game(7, 1_000);
+        assert!(!first.is_empty());
+        assert_eq!(easy_game(7, 1_000), first);
+        // Only the seed matters, not when the game was created
+        assert_eq!(easy_game(7, 5_000_000), first);
+    }
+
+    #[test]
+    fn canonical_hash_is_shared_by_rotations_and_reflections() {
+        let board = lopsided_board();
+        let hash = game_with_board(board.clone()).canonical_hash();
+
+        let mut rotated = board;
+        for _ in 0..4 {
+            rotated = rotate_board(&rotated);
+            assert_eq!(game_with_board(rotated.clone()).canonical_hash(), hash);
+            assert_eq!(game_with_board(mirror_board(&rotated)).canonical_hash(), hash);
+        }
+    }
+
+    #[test]
+    fn canonical_hash_tells_different_positions_apart() {
+        let mut corner = vec![vec![None; 3]; 3];
+        corner[0][0] = Some(Player::X);
+        let mut centre = vec![vec![None; 3]; 3];
+        centre[1][1] = Some(Player::X);
+        let mut other_player = vec![vec![None; 3]; 3];
+        other_player[0][0] = Some(Player::O);
+
+        let corner_hash = game_with_board(corner).canonical_hash();
+        assert_ne!(game_with_board(centre).canonical_hash(), corner_hash);
+        assert_ne!(game_with_board(other_player).canonical_hash(), corner_hash);
     }
 }